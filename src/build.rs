@@ -1,19 +1,36 @@
 //! 构建与渲染模块：
-//! - 解析主题模板并渲染首页、内网页与详情跳转页
+//! - 解析主题模板并渲染首页、内网页、详情跳转页与自定义页面
 //! - 生成 robots.txt、sitemap.xml
 //! - 处理 slug/UTM/风险标签等
+//! - 按 `details_format` 渲染链接详情与简介（Markdown/HTML/纯文本），并按允许列表清理 HTML
 
 use anyhow::{bail, Context, Result};
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
     fs,
     path::{Path, PathBuf},
 };
 use tera::{Context as TContext, Tera};
 
 use crate::{
-    config::{ChangeFreq, ColorScheme, Config, Layout, RiskLevel, SearchEngine, Site, UtmParams},
+    config::{
+        ChangeFreq, ColorScheme, Config, DetailsFormat, Layout, Link, RiskLevel, SearchEngine,
+        ShortLinkMode, Site, UtmParams,
+    },
+    csp,
+    deploy::{write_deploy_files, write_redirect_pages, Redirect},
+    fingerprint::{fingerprint_assets, AssetManifest},
     icons::{download_icons_concurrent, normalize_remote_icon},
+    markdown,
+    minify::Minifier,
+    pages::{LinkTarget, SitePages},
+    precache::collect_precache,
+    precompress,
+    sanitize::Sanitizers,
+    shortlinks::{self, ShortLink},
+    slugs::{assign_slugs, encode_path, SlugAssignment},
+    staging::StagingDir,
+    theme::ThemeChain,
     utils::{env_opt_string, env_opt_usize, hostname_from_url, safe_subpath},
 };
 
@@ -21,6 +38,7 @@ use crate::{
 #[allow(clippy::too_many_arguments)]
 pub(crate) fn build(
     mut config: Config,
+    config_dir: Option<&Path>,
     out_dir: &Path,
     static_dir: Option<&Path>,
     theme_cli: Option<&Path>,
@@ -33,6 +51,8 @@ pub(crate) fn build(
     build_version_opt: Option<String>,
     icon_dir_cli: Option<String>,
     icon_threads_cli: Option<usize>,
+    minify_cli: Option<bool>,
+    precompress_cli: Option<bool>,
 ) -> Result<()> {
     // 准备输出目录
     if !out_dir.exists() {
@@ -41,52 +61,34 @@ pub(crate) fn build(
     }
     // 计算站点根目录（支持 base_path 子路径），CLI 覆盖配置
     let base_path_effective = base_path_cli.or_else(|| config.site.base_path.clone());
-    let site_dir = match &base_path_effective {
+    let final_site_dir = match &base_path_effective {
         Some(bp) => match safe_subpath(bp) {
             Some(sub) => out_dir.join(sub),
             None => out_dir.to_path_buf(),
         },
         None => out_dir.to_path_buf(),
     };
-    if !site_dir.exists() {
-        fs::create_dir_all(&site_dir)
-            .with_context(|| format!("创建站点目录失败: {}", site_dir.display()))?;
-    }
+    // 先输出到暂存目录，全部成功后再替换正式目录；失败时保留上一次的输出
+    let mut staging = StagingDir::create(&final_site_dir)?;
+    let site_dir = staging.path().to_path_buf();
 
-    // 解析主题目录：CLI --theme > 配置 site.theme_dir > 默认 themes/default
-    let mut theme_dir = theme_cli
-        .map(|p| p.to_path_buf())
-        .or_else(|| config.site.theme_dir.as_ref().map(PathBuf::from))
-        .unwrap_or_else(|| PathBuf::from("themes/default"));
-    if !theme_dir.exists() {
-        // 兼容在工作区根目录运行：尝试 dove/<theme_dir>
-        let alt = Path::new("dove").join(&theme_dir);
-        if alt.exists() {
-            theme_dir = alt;
-        }
-    }
-    if !theme_dir.exists() {
-        bail!(
-            "主题目录不存在: {}。可用 --theme 指定或在 dove.yaml 的 site.theme_dir 配置。",
-            theme_dir.display()
-        );
+    let theme = resolve_theme(theme_cli, &config)?;
+    if theme.layers().len() > 1 || theme.uses_embedded() {
+        println!("🎨 主题: {}", theme.describe());
     }
 
-    // 拷贝主题 assets -> site_dir/assets
-    let theme_assets = theme_dir.join("assets");
-    if theme_assets.exists() {
-        let dest_assets = site_dir.join("assets");
-        if !dest_assets.exists() {
-            fs::create_dir_all(&dest_assets)?;
-        }
-        crate::init::copy_dir_all(&theme_assets, &dest_assets)?;
-
+    // 按继承链叠加拷贝主题 assets -> site_dir/assets（子主题覆盖父主题）
+    let dest_assets = site_dir.join("assets");
+    if theme.copy_assets(&dest_assets)? {
         // Copy sw.js to dist directory if it exists
-        let sw_js_path = theme_assets.join("sw.js");
+        let sw_js_path = dest_assets.join("sw.js");
         if sw_js_path.exists() {
             let dist_sw_js_path = site_dir.join("sw.js");
             std::fs::copy(&sw_js_path, &dist_sw_js_path)?;
-            println!("-> Copied sw.js to {}", dist_sw_js_path.display());
+            println!(
+                "-> Copied sw.js to {}",
+                final_site_dir.join("sw.js").display()
+            );
         }
     }
 
@@ -99,6 +101,14 @@ pub(crate) fn build(
         }
     }
 
+    // 压缩 CSS/JS 资源（需在计算指纹之前）：CLI/ENV > 配置 site.minify > 默认关闭
+    let minifier = Minifier::new(minify_cli.or(config.site.minify).unwrap_or(false));
+    minifier.minify_assets(&site_dir.join("assets"))?;
+
+    // 资源指纹：在静态资源覆盖之后计算，确保哈希对应最终内容
+    let asset_manifest = fingerprint_assets(&site_dir)?;
+    println!("🔖 资源指纹: build {}", asset_manifest.build_hash());
+
     // 并发预取远程图标，并回写为本地相对路径（失败则保持远程 URL）
     // 目标目录优先级：CLI > ENV > 默认；相对于站点根
     let icon_dir_rel: String = icon_dir_cli
@@ -183,11 +193,25 @@ pub(crate) fn build(
     // 构建时间（UTC，ISO 8601 简化至秒）
     let build_time = chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
 
+    // 自定义页面（pages/*.md）
+    let pages = SitePages::load(config.site.pages.as_ref())?;
+
+    // 外网链接 slug（go/<slug>/、短链接）：按锁文件沿用已分配的值
+    // 锁文件相对本地配置所在目录；远程配置时相对输出目录的上级（项目根）
+    let lock_base = config_dir
+        .or_else(|| out_dir.parent())
+        .unwrap_or(Path::new("."));
+    let slugs = assign_slugs(&config, generate_intermediate_page, lock_base)?;
+
     // 渲染 HTML via Tera 到 site_dir
-    let detail_records = render_with_theme(
+    let (detail_records, direct_links) = render_with_theme(
         &config,
-        &theme_dir,
+        &theme,
         &site_dir,
+        &asset_manifest,
+        &minifier,
+        &pages,
+        &slugs,
         !no_intranet,
         generate_intermediate_page,
         color_scheme_override,
@@ -204,18 +228,182 @@ pub(crate) fn build(
         &config.site,
         base_path_effective.as_deref(),
         &detail_records,
+        &pages,
         &build_time,
     )?;
 
-    println!("✅ 生成完成 -> {}", site_dir.display());
+    // 短链接 s/<slug>：跳转页需在 CSP 与预缓存之前写出
+    let mut redirects: Vec<Redirect> = Vec::new();
+    if let Some(ref short) = config.site.short_links {
+        let dir = short
+            .dir
+            .as_deref()
+            .map(|d| d.trim_matches('/'))
+            .filter(|d| !d.is_empty())
+            .unwrap_or(shortlinks::DEFAULT_DIR);
+        if matches!(dir, "go" | "intranet" | "assets") || dir.contains('/') {
+            bail!(
+                "短链接目录不可用: {}（需为单级目录，且不能是 go、intranet、assets）",
+                dir
+            );
+        }
+        if pages.pages().iter().any(|p| p.slug == dir) {
+            bail!("页面 slug 与短链接目录冲突: {}", dir);
+        }
+        let detail_urls: Vec<String> = detail_records
+            .iter()
+            .map(|d| apply_utm(&d.final_url, d.utm.as_ref()))
+            .collect();
+        let mut links: Vec<ShortLink> = detail_records
+            .iter()
+            .zip(&detail_urls)
+            .map(|(d, url)| ShortLink {
+                name: &d.name,
+                slug: &d.slug,
+                url,
+                aliases: &d.aliases,
+            })
+            .collect();
+        links.extend(direct_links.iter().map(|d| ShortLink {
+            name: &d.name,
+            slug: &d.slug,
+            url: &d.url,
+            aliases: &d.aliases,
+        }));
+        let short_redirects = shortlinks::collect(dir, &links, config.site.slug_strategy);
+        if matches!(short.mode, ShortLinkMode::Html | ShortLinkMode::Both) {
+            write_redirect_pages(&site_dir, &short_redirects)?;
+        }
+        println!("🔗 短链接: {} 个 -> {}/", short_redirects.len(), dir);
+        if matches!(short.mode, ShortLinkMode::Rules | ShortLinkMode::Both) {
+            redirects.extend(short_redirects);
+        }
+    }
+
+    // 内容安全策略：需在预缓存之前写入，保证预缓存修订号与最终页面一致
+    let csp_header = match config.site.csp {
+        Some(ref csp_settings) => csp::apply(&site_dir, csp_settings, &config.site)?,
+        None => None,
+    };
+
+    // 页面与图标全部写出后，按实际输出生成 Service Worker 预缓存清单
+    let site_sw_js = site_dir.join("sw.js");
+    if site_sw_js.exists() {
+        let precache = collect_precache(&site_dir, config.site.precache.as_ref(), &asset_manifest)?;
+        asset_manifest.inject_service_worker(&site_sw_js, &precache)?;
+    }
+
+    minifier.report();
+
+    // 预压缩（最后执行，确保覆盖注入后的 sw.js 与压缩后的页面）：CLI/ENV > 配置 site.precompress
+    if precompress_cli.or(config.site.precompress).unwrap_or(false) {
+        let min_size = config
+            .site
+            .precompress_min_size
+            .unwrap_or(precompress::DEFAULT_MIN_SIZE);
+        precompress::precompress_site(&site_dir, min_size)?;
+    }
+
+    // 部署文件（_headers/_redirects、vercel.json、nginx 片段）：平台从发布目录根读取，
+    // 设置 base_path 时写入输出目录根，规则路径带 base_path 前缀
+    let deploy = config.site.deploy.as_ref();
+    if deploy.is_some_and(|d| d.redirect_go.unwrap_or(true)) {
+        redirects.extend(direct_links.iter().map(|d| Redirect {
+            from: format!("go/{}/", d.slug),
+            to: d.url.clone(),
+        }));
+    }
+    // 锁文件中停用的旧 slug：指向当前中间页，未生成中间页时直接指向目标地址；
+    // 配置了 site.deploy 时写入重定向规则，否则生成跳转页
+    let retired: Vec<Redirect> = slugs
+        .retired()
+        .iter()
+        .filter_map(|r| {
+            let to = if detail_records.iter().any(|d| d.slug == r.current) {
+                format!("go/{}/", r.current)
+            } else {
+                direct_links
+                    .iter()
+                    .find(|d| d.slug == r.current)?
+                    .url
+                    .clone()
+            };
+            Some(Redirect {
+                from: format!("go/{}/", r.old),
+                to,
+            })
+        })
+        .collect();
+    if deploy.is_some() {
+        redirects.extend(retired);
+    } else {
+        write_redirect_pages(&site_dir, &retired)?;
+    }
+    // base_path 时部署文件位于输出根：同样先暂存，替换站点目录后再移入
+    let deploy_root = if final_site_dir == out_dir {
+        site_dir.clone()
+    } else {
+        staging.outside_dir(out_dir)?
+    };
+    write_deploy_files(
+        &deploy_root,
+        &site_dir,
+        base_path_effective.as_deref(),
+        deploy,
+        &asset_manifest,
+        csp_header.as_deref(),
+        &redirects,
+    )?;
+
+    staging
+        .commit()
+        .with_context(|| format!("发布构建结果失败: {}", final_site_dir.display()))?;
+    slugs.save_lock()?;
+    println!("✅ 生成完成 -> {}", final_site_dir.display());
     Ok(())
 }
 
+/// 解析主题：CLI --theme > 配置 site.theme_dir > 默认 themes/default；
+/// 默认主题（未指定或指向 `themes/default`）不在磁盘上时，使用内置默认主题
+pub(crate) fn resolve_theme(theme_cli: Option<&Path>, config: &Config) -> Result<ThemeChain> {
+    let explicit = theme_cli
+        .map(|p| p.to_path_buf())
+        .or_else(|| config.site.theme_dir.as_ref().map(PathBuf::from));
+    let mut theme_dir = explicit
+        .clone()
+        .unwrap_or_else(|| PathBuf::from("themes/default"));
+    if !theme_dir.exists() {
+        // 兼容在工作区根目录运行：尝试 dove/<theme_dir>
+        let alt = Path::new("dove").join(&theme_dir);
+        if alt.exists() {
+            theme_dir = alt;
+        }
+    }
+    if !theme_dir.exists() {
+        // 指向默认主题位置（含示例配置中的 `themes/default`）时回退到内置主题
+        if explicit
+            .as_deref()
+            .is_none_or(|p| p.ends_with("themes/default"))
+        {
+            return ThemeChain::embedded();
+        }
+        bail!(
+            "主题目录不存在: {}。可用 --theme 指定或在 dove.yaml 的 site.theme_dir 配置。",
+            theme_dir.display()
+        );
+    }
+    ThemeChain::resolve(&theme_dir)
+}
+
 #[allow(clippy::too_many_arguments)]
 fn render_with_theme(
     cfg: &Config,
-    theme_dir: &Path,
+    theme: &ThemeChain,
     out_dir: &Path,
+    assets: &AssetManifest,
+    minifier: &Minifier,
+    pages: &SitePages,
+    slugs: &SlugAssignment,
     generate_intranet: bool,
     generate_intermediate_page: bool,
     color_scheme_override: Option<ColorScheme>,
@@ -223,19 +411,27 @@ fn render_with_theme(
     desc_override: Option<String>,
     build_version: &str,
     build_time: &str,
-) -> Result<Vec<LinkDetail>> {
-    // 匹配主题模板目录
-    let pattern = theme_dir.join("templates").join("**/*");
-    let pattern_str = pattern.to_string_lossy().to_string();
-    let tera = Tera::new(&pattern_str).with_context(|| format!("加载模板失败: {}", pattern_str))?;
+) -> Result<(Vec<LinkDetail>, Vec<DirectLink>)> {
+    // 按主题继承链加载模板
+    let mut tera = theme.load_templates()?;
+    assets.register(&mut tera);
+    // 主题选项：清单默认值 + site.theme_options，以 `theme` 注入所有模板
+    let theme_options = theme.options(&cfg.site.theme_options)?;
+    // 链接文案：按格式渲染并清理 HTML（每个链接只处理一次，告警不重复）
+    let copies = render_link_copies(cfg)?;
 
     // 渲染外网(index.html)，按需渲染内网(intranet/index.html)
     let title_ref = title_override.as_deref();
     let desc_ref = desc_override.as_deref();
-    let external_details = render_one(
+    let (external_details, direct_links) = render_one(
         &tera,
         cfg,
         out_dir,
+        minifier,
+        &theme_options,
+        pages,
+        &copies,
+        slugs,
         NetMode::External,
         generate_intranet,
         generate_intermediate_page,
@@ -250,6 +446,9 @@ fn render_with_theme(
             &tera,
             cfg,
             out_dir,
+            minifier,
+            &theme_options,
+            pages,
             &external_details,
             color_scheme_override,
             title_ref,
@@ -257,18 +456,17 @@ fn render_with_theme(
             build_version,
             build_time,
         )?;
-    } else {
-        let go_root = out_dir.join("go");
-        if go_root.exists() {
-            fs::remove_dir_all(&go_root)
-                .with_context(|| format!("清理旧的 go/ 目录失败: {}", go_root.display()))?;
-        }
     }
     if generate_intranet {
         let _internals = render_one(
             &tera,
             cfg,
             out_dir,
+            minifier,
+            &theme_options,
+            pages,
+            &copies,
+            slugs,
             NetMode::Intranet,
             generate_intranet,
             generate_intermediate_page,
@@ -279,7 +477,23 @@ fn render_with_theme(
             build_time,
         )?;
     }
-    Ok(external_details)
+    if !pages.is_empty() {
+        render_pages(
+            &tera,
+            cfg,
+            out_dir,
+            minifier,
+            &theme_options,
+            pages,
+            &link_targets(&external_details, &direct_links),
+            color_scheme_override,
+            title_ref,
+            desc_ref,
+            build_version,
+            build_time,
+        )?;
+    }
+    Ok((external_details, direct_links))
 }
 
 #[derive(Clone, Copy)]
@@ -293,7 +507,8 @@ struct LinkDetail {
     slug: String,
     name: String,
     intro: String,
-    details: Option<String>,
+    intro_html: Option<String>,
+    details_html: Option<String>,
     icon: Option<String>,
     host: String,
     final_url: String,
@@ -303,6 +518,17 @@ struct LinkDetail {
     s_lastmod: Option<String>,
     s_changefreq: Option<ChangeFreq>,
     s_priority: Option<f32>,
+    aliases: Vec<String>,
+}
+
+/// 未生成中间页的外网链接（部署文件可将 `go/<slug>/` 重定向到目标地址）
+#[derive(Clone)]
+pub(crate) struct DirectLink {
+    pub(crate) slug: String,
+    /// 目标地址（已附加 UTM 参数）
+    pub(crate) url: String,
+    name: String,
+    aliases: Vec<String>,
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -310,6 +536,11 @@ fn render_one(
     tera: &Tera,
     cfg: &Config,
     out_dir: &Path,
+    minifier: &Minifier,
+    theme_options: &BTreeMap<String, serde_yaml::Value>,
+    pages: &SitePages,
+    copies: &[Vec<LinkCopy>],
+    slugs: &SlugAssignment,
     mode: NetMode,
     has_intranet: bool,
     generate_intermediate_page: bool,
@@ -318,11 +549,12 @@ fn render_one(
     desc_override: Option<&str>,
     build_version: &str,
     build_time: &str,
-) -> Result<Vec<LinkDetail>> {
+) -> Result<(Vec<LinkDetail>, Vec<DirectLink>)> {
     let mut ctx = TContext::new();
     // Build/version info from caller (CI/CLI), already resolved
     ctx.insert("build_version", &build_version);
     ctx.insert("build_time", &build_time);
+    ctx.insert("theme", theme_options);
 
     let site_title = title_override.unwrap_or(&cfg.site.title);
     let site_desc = desc_override.unwrap_or(&cfg.site.description);
@@ -351,6 +583,7 @@ fn render_one(
     ctx.insert("asset_prefix", &asset_prefix);
     ctx.insert("root_prefix", &root_prefix);
     ctx.insert("service_worker_path", &service_worker_path);
+    ctx.insert("nav_pages", &pages.nav(&root_prefix, None));
     // 内/外网切换链接与标签
     let (network_switch_href, mode_other_label) = match mode {
         NetMode::External => ("intranet/", "内网"),
@@ -410,6 +643,7 @@ fn render_one(
         href: String,
         display_url: String,
         desc: String,
+        desc_html: Option<String>,
         icon: Option<String>,
         host: String,
     }
@@ -421,14 +655,15 @@ fn render_one(
         links: Vec<RLink>,
     }
 
-    let mut used_slugs: HashSet<String> = HashSet::new();
-    let mut name_counts: HashMap<String, u32> = HashMap::new();
     let mut details: Vec<LinkDetail> = Vec::new();
+    let mut direct: Vec<DirectLink> = Vec::new();
     let mut rgroups: Vec<RGroup> = Vec::new();
     let mut categories: Vec<String> = Vec::new();
-    for g in &cfg.groups {
+    for (gi, (g, group_copies)) in cfg.groups.iter().zip(copies).enumerate() {
         let mut rlinks = Vec::new();
-        for l in &g.links {
+        for (li, (l, copy)) in g.links.iter().zip(group_copies).enumerate() {
+            let intro = copy.intro.clone();
+            let intro_html = copy.intro_html.clone();
             match mode {
                 NetMode::External => {
                     // 仅当存在外网地址时参与外网页面与详情页
@@ -448,24 +683,14 @@ fn render_one(
                     let link_intermediate =
                         l.intermediate_page.unwrap_or(generate_intermediate_page);
                     let mut href = final_url.clone();
+                    let utm = l
+                        .utm
+                        .clone()
+                        .or_else(|| cfg.site.redirect.as_ref().and_then(|r| r.utm.clone()));
+                    let slug = slugs.get(gi, li).unwrap_or_default().to_string();
                     if link_intermediate {
-                        let base_slug = if let Some(user_slug) = &l.slug {
-                            slugify(user_slug)
-                        } else {
-                            // 默认：按 name 生成；若 name 重复，则使用 name+host 组合
-                            let key = l.name.to_lowercase();
-                            let entry = name_counts.entry(key).or_insert(0);
-                            *entry += 1;
-                            if *entry == 1 {
-                                slugify(&l.name)
-                            } else if !host.is_empty() {
-                                slugify(&format!("{}-{}", l.name, host))
-                            } else {
-                                slugify(&l.name)
-                            }
-                        };
-                        let slug = unique_slug(&base_slug, &mut used_slugs);
-                        href = format!("/go/{}/", slug);
+                        // 使用相对路径，站点部署在 base_path 子路径下时同样可用
+                        href = format!("{}go/{}/", root_prefix, encode_path(&slug));
                         let delay = cfg
                             .site
                             .redirect
@@ -475,15 +700,12 @@ fn render_one(
                         let risk = l
                             .risk
                             .or_else(|| cfg.site.redirect.as_ref().and_then(|r| r.default_risk));
-                        let utm = l
-                            .utm
-                            .clone()
-                            .or_else(|| cfg.site.redirect.as_ref().and_then(|r| r.utm.clone()));
                         details.push(LinkDetail {
                             slug,
                             name: l.name.clone(),
-                            intro: l.intro.clone(),
-                            details: l.details.clone(),
+                            intro: intro.clone(),
+                            intro_html: intro_html.clone(),
+                            details_html: copy.details_html.clone(),
                             icon: l.icon.clone(),
                             host: host.clone(),
                             final_url: final_url.clone(),
@@ -493,6 +715,14 @@ fn render_one(
                             s_lastmod: l.lastmod.clone(),
                             s_changefreq: l.changefreq,
                             s_priority: l.priority,
+                            aliases: l.aliases.clone(),
+                        });
+                    } else {
+                        direct.push(DirectLink {
+                            slug,
+                            url: apply_utm(&final_url, utm.as_ref()),
+                            name: l.name.clone(),
+                            aliases: l.aliases.clone(),
                         });
                     }
                     let icon_res = l
@@ -503,7 +733,8 @@ fn render_one(
                         name: l.name.clone(),
                         href: href.clone(),
                         display_url: final_url.clone(),
-                        desc: l.intro.clone(),
+                        desc: intro,
+                        desc_html: intro_html,
                         icon: icon_res,
                         host: host.clone(),
                     });
@@ -527,7 +758,8 @@ fn render_one(
                         name: l.name.clone(),
                         href,
                         display_url,
-                        desc: l.intro.clone(),
+                        desc: intro,
+                        desc_html: intro_html,
                         icon: icon_res,
                         host,
                     });
@@ -574,8 +806,9 @@ fn render_one(
             )
         }
     };
-    fs::write(&target_path, html).with_context(|| format!("写入 {} 失败", display_name))?;
-    Ok(details)
+    fs::write(&target_path, minifier.html(html))
+        .with_context(|| format!("写入 {} 失败", display_name))?;
+    Ok((details, direct))
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -583,6 +816,9 @@ fn render_link_details(
     tera: &Tera,
     cfg: &Config,
     out_dir: &Path,
+    minifier: &Minifier,
+    theme_options: &BTreeMap<String, serde_yaml::Value>,
+    pages: &SitePages,
     links: &[LinkDetail],
     color_scheme_override: Option<ColorScheme>,
     title_override: Option<&str>,
@@ -590,12 +826,6 @@ fn render_link_details(
     build_version: &str,
     build_time: &str,
 ) -> Result<()> {
-    let go_root = out_dir.join("go");
-    if go_root.exists() {
-        fs::remove_dir_all(&go_root)
-            .with_context(|| format!("清理旧的 go/ 目录失败: {}", go_root.display()))?;
-    }
-
     let site_title = title_override.unwrap_or(&cfg.site.title);
     let site_desc = desc_override.unwrap_or(&cfg.site.description);
     let scheme = match color_scheme_override.unwrap_or(cfg.site.color_scheme) {
@@ -628,6 +858,7 @@ fn render_link_details(
         let mut ctx = TContext::new();
         ctx.insert("build_version", &build_version);
         ctx.insert("build_time", &build_time);
+        ctx.insert("theme", theme_options);
         ctx.insert("site_title", &site_title);
         ctx.insert("site_desc", &site_desc);
         ctx.insert("color_scheme", &scheme);
@@ -636,18 +867,23 @@ fn render_link_details(
         if let Some(ref base_url) = cfg.site.base_url {
             ctx.insert("base_url", base_url);
             // 构建详情页的完整URL
-            let detail_url = format!("{}/go/{}/", base_url.trim_end_matches('/'), d.slug);
+            let detail_url = format!(
+                "{}/go/{}/",
+                base_url.trim_end_matches('/'),
+                encode_path(&d.slug)
+            );
             ctx.insert("site_url", &detail_url);
         }
         if let Some(og) = og_image_url(cfg, true) {
             ctx.insert("og_image", &og);
         }
         ctx.insert("categories", &categories);
+        ctx.insert("nav_pages", &pages.nav("../../", None));
         ctx.insert("link_name", &d.name);
         ctx.insert("link_intro", &d.intro);
-        // 详情 HTML：若配置了 details，用原样 HTML；否则使用简介文本（将在模板中 escape）
-        let details_html: Option<String> = d.details.clone();
-        ctx.insert("link_details_html", &details_html);
+        ctx.insert("link_intro_html", &d.intro_html);
+        // 详情 HTML：已按 details_format 渲染；未配置 details 时模板回退为简介
+        ctx.insert("link_details_html", &d.details_html);
         let icon_href: Option<String> = d.icon.as_ref().map(|s| resolve_icon_for_detail(s));
         ctx.insert("link_icon", &icon_href);
         ctx.insert("link_host", &d.host);
@@ -679,44 +915,183 @@ fn render_link_details(
         if !dir.exists() {
             fs::create_dir_all(&dir)?;
         }
-        fs::write(dir.join("index.html"), html)
+        fs::write(dir.join("index.html"), minifier.html(html))
             .with_context(|| format!("写入详情页失败: go/{}/index.html", d.slug))?;
     }
     Ok(())
 }
 
-fn slugify(input: &str) -> String {
-    let mut s = String::with_capacity(input.len());
-    let mut prev_dash = false;
-    for ch in input.chars() {
-        let c = ch.to_ascii_lowercase();
-        if c.is_ascii_alphanumeric() {
-            s.push(c);
-            prev_dash = false;
-        } else if !prev_dash && !s.is_empty() {
-            s.push('-');
-            prev_dash = true;
+/// 渲染并清理后的链接文案
+struct LinkCopy {
+    intro: String,
+    intro_html: Option<String>,
+    details_html: Option<String>,
+}
+
+/// 按 `[分组][链接]` 渲染全部链接文案
+fn render_link_copies(cfg: &Config) -> Result<Vec<Vec<LinkCopy>>> {
+    let sanitizers = Sanitizers::new(cfg.site.sanitize.as_ref());
+    cfg.groups
+        .iter()
+        .map(|g| {
+            g.links
+                .iter()
+                .map(|l| render_link_copy(l, &cfg.site, &sanitizers))
+                .collect()
+        })
+        .collect()
+}
+
+fn render_link_copy(l: &Link, site: &Site, sanitizers: &Sanitizers) -> Result<LinkCopy> {
+    let (intro, mut intro_html) = render_intro(l, site);
+    let mut details_html = render_details(l, site)?;
+    if let Some(sanitizer) = sanitizers.for_link(l) {
+        let mut stripped: BTreeSet<String> = BTreeSet::new();
+        for html in [&mut intro_html, &mut details_html].into_iter().flatten() {
+            let (cleaned, removed) = sanitizer.clean(html);
+            *html = cleaned;
+            stripped.extend(removed);
+        }
+        if !stripped.is_empty() {
+            let list: Vec<&str> = stripped.iter().map(String::as_str).collect();
+            eprintln!(
+                "警告: 链接 {} 的详情/简介中以下内容已被清理: {}",
+                l.name,
+                list.join(", ")
+            );
         }
     }
-    while s.ends_with('-') {
-        s.pop();
-    }
-    if s.is_empty() {
-        "link".to_string()
+    Ok(LinkCopy {
+        intro,
+        intro_html,
+        details_html,
+    })
+}
+
+fn details_format(l: &Link, site: &Site) -> DetailsFormat {
+    l.details_format.or(site.details_format).unwrap_or_default()
+}
+
+/// 简介：Markdown 格式按行内渲染，返回 (纯文本, HTML)；其余格式为纯文本
+fn render_intro(l: &Link, site: &Site) -> (String, Option<String>) {
+    if details_format(l, site) == DetailsFormat::Markdown && !l.intro.trim().is_empty() {
+        let (html, plain) = markdown::render_inline(&l.intro);
+        (plain, Some(html))
     } else {
-        s
+        (l.intro.clone(), None)
     }
 }
 
-fn unique_slug(base: &str, used: &mut HashSet<String>) -> String {
-    let mut slug = base.to_string();
-    let mut i = 2;
-    while used.contains(&slug) {
-        slug = format!("{}-{}", base, i);
-        i += 1;
+/// 详情 HTML：按格式渲染（Markdown 含代码高亮，HTML 原样，纯文本转义分段）
+fn render_details(l: &Link, site: &Site) -> Result<Option<String>> {
+    let Some(details) = l.details.as_deref().filter(|d| !d.trim().is_empty()) else {
+        return Ok(None);
+    };
+    let html = match details_format(l, site) {
+        DetailsFormat::Markdown => {
+            markdown::render(details).with_context(|| format!("渲染链接 {} 的详情失败", l.name))?
+        }
+        DetailsFormat::Html => details.to_string(),
+        DetailsFormat::Text => markdown::text_to_html(details),
+    };
+    Ok(Some(html))
+}
+
+/// 页面中 `link:<slug>` 可引用的目标：生成中间页的链接指向 `go/<slug>/`，其余外网链接直接指向目标地址
+fn link_targets(details: &[LinkDetail], direct: &[DirectLink]) -> HashMap<String, LinkTarget> {
+    details
+        .iter()
+        .map(|d| (d.slug.clone(), LinkTarget::Detail(d.slug.clone())))
+        .chain(
+            direct
+                .iter()
+                .map(|d| (d.slug.clone(), LinkTarget::Url(d.url.clone()))),
+        )
+        .collect()
+}
+
+#[allow(clippy::too_many_arguments)]
+fn render_pages(
+    tera: &Tera,
+    cfg: &Config,
+    out_dir: &Path,
+    minifier: &Minifier,
+    theme_options: &BTreeMap<String, serde_yaml::Value>,
+    pages: &SitePages,
+    links: &HashMap<String, LinkTarget>,
+    color_scheme_override: Option<ColorScheme>,
+    title_override: Option<&str>,
+    desc_override: Option<&str>,
+    build_version: &str,
+    build_time: &str,
+) -> Result<()> {
+    let site_title = title_override.unwrap_or(&cfg.site.title);
+    let site_desc = desc_override.unwrap_or(&cfg.site.description);
+    let scheme = match color_scheme_override.unwrap_or(cfg.site.color_scheme) {
+        ColorScheme::Auto => "auto",
+        ColorScheme::Light => "light",
+        ColorScheme::Dark => "dark",
+    };
+
+    for p in pages.pages() {
+        if !tera.get_template_names().any(|n| n == p.template) {
+            bail!(
+                "主题缺少页面模板 {}（页面 {}）。请在主题 templates/ 中添加，或让主题继承 default。",
+                p.template,
+                p.slug
+            );
+        }
+        let mut ctx = TContext::new();
+        ctx.insert("build_version", &build_version);
+        ctx.insert("build_time", &build_time);
+        ctx.insert("theme", theme_options);
+        ctx.insert("site_title", &site_title);
+        ctx.insert("site_desc", &site_desc);
+        ctx.insert("color_scheme", &scheme);
+        // 页面位于 <slug>/，静态资源与站点根均需上溯一级
+        ctx.insert("asset_prefix", "../");
+        ctx.insert("root_prefix", "../");
+        ctx.insert("service_worker_path", "../sw.js");
+        ctx.insert("nav_pages", &pages.nav("../", Some(&p.slug)));
+
+        if let Some(base) = cfg.site.base_url.as_deref() {
+            let page_url = build_page_url(
+                Some(base),
+                cfg.site.base_path.as_deref(),
+                &format!("{}/", p.slug),
+            );
+            ctx.insert("canonical_url", &page_url);
+            ctx.insert("site_url", &page_url);
+        }
+        if let Some(og) = og_image_url(cfg, false) {
+            ctx.insert("og_image", &og);
+        }
+        ctx.insert("page_slug", &p.slug);
+        ctx.insert("page_title", &p.title);
+        ctx.insert("page_description", &p.description);
+        ctx.insert("page_lastmod", &p.lastmod);
+        ctx.insert("page_content", &pages.render_content(p, links)?);
+        // 可选：百度统计（Tongji）站点 ID，用于注入 hm.js
+        if let Some(ref id) = cfg.site.baidu_tongji_id {
+            if !id.trim().is_empty() {
+                ctx.insert("baidu_tongji_id", id);
+            }
+        }
+        // 可选：Google Analytics（GA4）Measurement ID，用于注入 gtag.js
+        if let Some(ref gid) = cfg.site.google_analytics_id {
+            if !gid.trim().is_empty() {
+                ctx.insert("google_analytics_id", gid);
+            }
+        }
+        let html = tera
+            .render(&p.template, &ctx)
+            .with_context(|| format!("渲染模板 {} 失败（页面 {}）", p.template, p.slug))?;
+        let dir = out_dir.join(&p.slug);
+        fs::create_dir_all(&dir)?;
+        fs::write(dir.join("index.html"), minifier.html(html))
+            .with_context(|| format!("写入页面失败: {}/index.html", p.slug))?;
     }
-    used.insert(slug.clone());
-    slug
+    Ok(())
 }
 
 fn apply_utm(url_str: &str, utm: Option<&UtmParams>) -> String {
@@ -775,6 +1150,7 @@ fn write_sitemap(
     site: &Site,
     base_path: Option<&str>,
     details: &[LinkDetail],
+    pages: &SitePages,
     build_time: &str,
 ) -> Result<()> {
     // Helper to join base_url + base_path + subpath
@@ -834,7 +1210,7 @@ fn write_sitemap(
     ));
     // 详情页
     for d in details {
-        let sub = format!("go/{}/", d.slug);
+        let sub = format!("go/{}/", encode_path(&d.slug));
         urls.push((
             url_join(site.base_url.as_deref(), base_path, &sub),
             d.s_lastmod.clone(),
@@ -842,6 +1218,20 @@ fn write_sitemap(
             sanitize_priority(d.s_priority),
         ));
     }
+    // 自定义页面
+    for p in pages.pages().iter().filter(|p| p.sitemap) {
+        let sub = format!("{}/", p.slug);
+        urls.push((
+            url_join(site.base_url.as_deref(), base_path, &sub),
+            p.lastmod.clone(),
+            p.changefreq
+                .or_else(|| site.sitemap.as_ref().and_then(|s| s.default_changefreq)),
+            sanitize_priority(
+                p.priority
+                    .or_else(|| site.sitemap.as_ref().and_then(|s| s.default_priority)),
+            ),
+        ));
+    }
 
     // 组装 XML
     let mut xml = String::new();
@@ -978,9 +1368,7 @@ fn resolve_icon_for_page(icon: &str, asset_prefix: &str) -> String {
         } else {
             format!("{}{}", asset_prefix, trimmed)
         }
-    } else if s.starts_with("../") || s.starts_with("./") {
-        s.to_string()
-    } else if asset_prefix.is_empty() {
+    } else if s.starts_with("../") || s.starts_with("./") || asset_prefix.is_empty() {
         s.to_string()
     } else {
         format!("{}{}", asset_prefix, s)
@@ -81,6 +81,24 @@ pub(crate) enum Command {
             action = clap::ArgAction::Set
         )]
         generate_intermediate_page: Option<bool>,
+        /// 压缩输出的 HTML 与 CSS/JS 资源（覆盖配置中的 site.minify）
+        #[arg(
+            long,
+            value_name = "BOOL",
+            num_args = 0..=1,
+            default_missing_value = "true",
+            action = clap::ArgAction::Set
+        )]
+        minify: Option<bool>,
+        /// 为 HTML/CSS/JS/SVG/JSON 生成 .gz/.br 预压缩文件（覆盖配置中的 site.precompress）
+        #[arg(
+            long,
+            value_name = "BOOL",
+            num_args = 0..=1,
+            default_missing_value = "true",
+            action = clap::ArgAction::Set
+        )]
+        precompress: Option<bool>,
     },
     /// 初始化示例配置与静态资源
     Init {
@@ -91,6 +109,14 @@ pub(crate) enum Command {
         #[arg(value_name = "DIR")]
         dir: Option<PathBuf>,
     },
+    /// 管理项目内的主题：安装、列出、更新
+    Theme {
+        /// 主题目录（默认 themes）
+        #[arg(long, value_name = "DIR", default_value = "themes", global = true)]
+        dir: PathBuf,
+        #[command(subcommand)]
+        action: ThemeAction,
+    },
     /// 预览生成结果（本地静态文件服务）
     Preview {
         /// 指定服务目录（优先于根据配置推导的 dist/<base_path>）
@@ -136,6 +162,21 @@ pub(crate) enum Command {
         /// 启动后自动在浏览器打开
         #[arg(long)]
         open: bool,
+        /// 监听所有网卡，打印局域网访问地址与终端二维码（便于手机访问）
+        #[arg(long)]
+        lan: bool,
+        /// 以自动生成的自签名证书提供 HTTPS（便于在手机上测试 Service Worker/PWA）
+        #[cfg(feature = "https")]
+        #[arg(long)]
+        https: bool,
+        /// 模拟网络：按映射文件把内网主机链接改写到本地替身（默认 dove.preview.yaml）
+        #[arg(
+            long,
+            value_name = "FILE",
+            num_args = 0..=1,
+            default_missing_value = "dove.preview.yaml"
+        )]
+        simulate_network: Option<PathBuf>,
         /// 覆盖页面配色方案（auto|light|dark）
         #[arg(long, value_name = "SCHEME")]
         color_scheme: Option<String>,
@@ -163,5 +204,50 @@ pub(crate) enum Command {
             action = clap::ArgAction::Set
         )]
         generate_intermediate_page: Option<bool>,
+        /// 压缩输出的 HTML 与 CSS/JS 资源（覆盖配置中的 site.minify）
+        #[arg(
+            long,
+            value_name = "BOOL",
+            num_args = 0..=1,
+            default_missing_value = "true",
+            action = clap::ArgAction::Set
+        )]
+        minify: Option<bool>,
+        /// 为 HTML/CSS/JS/SVG/JSON 生成 .gz/.br 预压缩文件（覆盖配置中的 site.precompress）
+        #[arg(
+            long,
+            value_name = "BOOL",
+            num_args = 0..=1,
+            default_missing_value = "true",
+            action = clap::ArgAction::Set
+        )]
+        precompress: Option<bool>,
+    },
+}
+
+/// `dove theme` 子命令
+#[derive(Subcommand, Debug)]
+pub(crate) enum ThemeAction {
+    /// 安装主题：本地目录、压缩包（.zip/.tar.gz/.tgz/.tar，URL 需 remote 特性）或 git 仓库（可用 `#<分支或标签>` 指定版本）
+    Install {
+        /// 主题来源
+        #[arg(value_name = "PATH|ARCHIVE|GIT_URL")]
+        source: String,
+        /// 安装后的主题名（默认取主题清单 name 或来源名）
+        #[arg(long)]
+        name: Option<String>,
+        /// 覆盖已存在的同名主题
+        #[arg(long)]
+        force: bool,
+    },
+    /// 列出已安装的主题（版本、内容哈希、来源）
+    List,
+    /// 按锁文件记录的来源更新主题（默认全部）
+    Update {
+        /// 只更新指定主题
+        name: Option<String>,
+        /// 覆盖有本地改动的主题
+        #[arg(long)]
+        force: bool,
     },
 }
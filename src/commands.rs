@@ -1,6 +1,6 @@
 //! 命令调度模块：
 //! - 接收解析好的 CLI 参数，计算“有效参数”
-//! - 调用配置加载、构建、预览、初始化等模块
+//! - 调用配置加载、构建、预览、初始化、主题管理等模块
 
 use anyhow::{Context, Result};
 use std::path::PathBuf;
@@ -10,8 +10,14 @@ use crate::{
     cli::{Cli, Command},
     config::{self, Config},
     init::init_scaffold,
+    lan::bind_all_interfaces,
     preview::preview_watch_and_serve,
-    utils::{env_bool_truthy, env_opt_path, env_opt_string, env_opt_usize, parse_color_scheme},
+    serve::mount_path,
+    theme_pkg::run_theme,
+    utils::{
+        env_bool_truthy, env_opt_path, env_opt_string, env_opt_usize, parse_color_scheme,
+        safe_subpath,
+    },
 };
 
 /// 运行指定的子命令
@@ -40,6 +46,8 @@ pub(crate) fn run(cli: Cli) -> Result<()> {
             icon_dir,
             icon_threads,
             generate_intermediate_page: generate_intermediate_page_cli,
+            minify,
+            precompress,
         } => {
             // 环境变量覆盖（若 CLI 未指定）
             let env_input = env_opt_path("DOVE_INPUT");
@@ -65,6 +73,8 @@ pub(crate) fn run(cli: Cli) -> Result<()> {
             let env_icon_dir = env_opt_string("DOVE_ICON_DIR");
             let env_icon_threads = env_opt_usize("DOVE_ICON_THREADS");
             let env_generate_intermediate_page = env_bool_truthy("DOVE_GENERATE_INTERMEDIATE_PAGE");
+            let env_minify = env_bool_truthy("DOVE_MINIFY");
+            let env_precompress = env_bool_truthy("DOVE_PRECOMPRESS");
 
             let mut effective_input = input.or(env_input);
             let effective_input_url = input_url.or(env_input_url);
@@ -98,6 +108,8 @@ pub(crate) fn run(cli: Cli) -> Result<()> {
             let effective_generate_intermediate_page = generate_intermediate_page_cli
                 .or(env_generate_intermediate_page)
                 .unwrap_or(true);
+            let effective_minify = minify.or(env_minify);
+            let effective_precompress = precompress.or(env_precompress);
 
             // 当提供了 URL/Gist 时，忽略显式/环境的本地 input 路径，使 URL/Gist 优先生效
             if effective_input_url.is_some() || effective_gist_id.is_some() {
@@ -123,6 +135,7 @@ pub(crate) fn run(cli: Cli) -> Result<()> {
             let out_dir = effective_out;
             build(
                 config,
+                loaded_cfg.source.local_dir().as_deref(),
                 &out_dir,
                 effective_static.as_deref(),
                 effective_theme.as_deref(),
@@ -135,12 +148,15 @@ pub(crate) fn run(cli: Cli) -> Result<()> {
                 build_version,
                 effective_icon_dir,
                 effective_icon_threads,
+                effective_minify,
+                effective_precompress,
             )
         }
         Command::Init { force, dir } => {
             let dir = dir.unwrap_or_else(|| PathBuf::from("."));
             init_scaffold(&dir, force)
         }
+        Command::Theme { dir, action } => run_theme(&dir, action),
         Command::Preview {
             dir,
             addr,
@@ -161,6 +177,10 @@ pub(crate) fn run(cli: Cli) -> Result<()> {
             base_path,
             no_intranet,
             open,
+            lan,
+            #[cfg(feature = "https")]
+            https,
+            simulate_network,
             color_scheme,
             title,
             description,
@@ -168,9 +188,15 @@ pub(crate) fn run(cli: Cli) -> Result<()> {
             icon_dir,
             icon_threads,
             generate_intermediate_page: generate_intermediate_page_cli,
+            minify,
+            precompress,
         } => {
             // 环境变量
             let env_addr = env_opt_string("DOVE_PREVIEW_ADDR");
+            let env_lan = env_bool_truthy("DOVE_PREVIEW_LAN").unwrap_or(false);
+            let env_simulate_network = env_opt_path("DOVE_PREVIEW_SIMULATE");
+            #[cfg(feature = "https")]
+            let env_https = env_bool_truthy("DOVE_PREVIEW_HTTPS").unwrap_or(false);
             let env_input = env_opt_path("DOVE_INPUT");
             let env_input_url =
                 env_opt_string("DOVE_INPUT_URL").or(env_opt_string("DOVE_GIST_URL"));
@@ -194,10 +220,23 @@ pub(crate) fn run(cli: Cli) -> Result<()> {
             let env_icon_dir = env_opt_string("DOVE_ICON_DIR");
             let env_icon_threads = env_opt_usize("DOVE_ICON_THREADS");
             let env_generate_intermediate_page = env_bool_truthy("DOVE_GENERATE_INTERMEDIATE_PAGE");
+            let env_minify = env_bool_truthy("DOVE_MINIFY");
+            let env_precompress = env_bool_truthy("DOVE_PRECOMPRESS");
 
+            let effective_lan = lan || env_lan;
+            #[cfg(feature = "https")]
+            let effective_https = https || env_https;
+            #[cfg(not(feature = "https"))]
+            let effective_https = false;
             let effective_addr = addr
                 .or(env_addr)
                 .unwrap_or_else(|| "127.0.0.1:8787".to_string());
+            // 局域网模式监听所有网卡，端口沿用指定值
+            let effective_addr = if effective_lan {
+                bind_all_interfaces(&effective_addr)
+            } else {
+                effective_addr
+            };
             let mut effective_input = input.or(env_input);
             let effective_input_url = input_url.or(env_input_url);
             #[cfg(feature = "remote")]
@@ -230,6 +269,8 @@ pub(crate) fn run(cli: Cli) -> Result<()> {
             let effective_generate_intermediate_page = generate_intermediate_page_cli
                 .or(env_generate_intermediate_page)
                 .unwrap_or(true);
+            let effective_minify = minify.or(env_minify);
+            let effective_precompress = precompress.or(env_precompress);
 
             // 当提供了 URL/Gist 时，忽略显式/环境的本地 input 路径，使 URL/Gist 优先生效
             if effective_input_url.is_some() || effective_gist_id.is_some() {
@@ -254,6 +295,7 @@ pub(crate) fn run(cli: Cli) -> Result<()> {
                     .with_context(|| "解析 YAML 失败（预览构建）")?;
                 build(
                     config,
+                    loaded_cfg.source.local_dir().as_deref(),
                     &effective_out,
                     effective_static.as_deref(),
                     effective_theme.as_deref(),
@@ -266,48 +308,42 @@ pub(crate) fn run(cli: Cli) -> Result<()> {
                     build_version.clone(),
                     effective_icon_dir.clone(),
                     effective_icon_threads,
+                    effective_minify,
+                    effective_precompress,
                 )?;
             }
 
-            // 计算服务目录
-            let serve_dir = if let Some(d) = dir {
-                d
+            // 计算服务目录与挂载路径：与部署一致，站点位于 `/<base_path>/` 下
+            let (serve_dir, mount) = if let Some(d) = dir {
+                // 显式指定目录时按原样挂载在根路径
+                (d, mount_path(None))
             } else {
-                // 尝试从配置推导 base_path
-                let loaded_opt = config::load_config(
-                    effective_input.as_deref(),
-                    effective_input_url.as_deref(),
-                    effective_gist_id.as_deref(),
-                    effective_gist_file.as_deref(),
-                    effective_github_token.as_deref(),
-                    effective_auth_scheme.as_deref(),
-                );
-                match loaded_opt.and_then(|lc| {
-                    serde_yaml::from_str::<Config>(&lc.text)
-                        .map(|c| (lc, c))
-                        .map_err(anyhow::Error::from)
-                }) {
-                    Ok((_lc, cfg)) => {
-                        let mut d = effective_out.clone();
-                        if let Some(bp) = cfg.site.base_path.as_deref() {
-                            for seg in bp.split('/') {
-                                let t = seg.trim();
-                                if t.is_empty() || t == "." || t == ".." {
-                                    continue;
-                                }
-                                d.push(t);
-                            }
-                        }
-                        d
-                    }
-                    Err(_) => effective_out.clone(),
-                }
+                // 尝试从配置推导 base_path（CLI/环境变量优先）
+                let cfg_base_path = effective_base_path.clone().or_else(|| {
+                    config::load_config(
+                        effective_input.as_deref(),
+                        effective_input_url.as_deref(),
+                        effective_gist_id.as_deref(),
+                        effective_gist_file.as_deref(),
+                        effective_github_token.as_deref(),
+                        effective_auth_scheme.as_deref(),
+                    )
+                    .ok()
+                    .and_then(|lc| serde_yaml::from_str::<Config>(&lc.text).ok())
+                    .and_then(|cfg| cfg.site.base_path)
+                });
+                let d = match cfg_base_path.as_deref().and_then(safe_subpath) {
+                    Some(sub) => effective_out.join(sub),
+                    None => effective_out.clone(),
+                };
+                (d, mount_path(cfg_base_path.as_deref()))
             };
 
             // 启动预览
             preview_watch_and_serve(
                 serve_dir,
                 effective_addr,
+                mount,
                 effective_input,
                 effective_input_url,
                 effective_gist_id,
@@ -327,6 +363,11 @@ pub(crate) fn run(cli: Cli) -> Result<()> {
                 build_version,
                 effective_icon_dir,
                 effective_icon_threads,
+                effective_minify,
+                effective_precompress,
+                effective_lan,
+                effective_https,
+                simulate_network.or(env_simulate_network),
             )
         }
     }
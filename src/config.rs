@@ -2,11 +2,13 @@
 //! - 定义 `Config`/`Site`/`Group`/`Link` 等数据结构
 //! - 提供 `load_config` 支持本地文件/URL/Gist（三者按优先级）
 //! - 暴露配置来源信息，便于日志打印
+//! - 记录参与加载的本地文件与远程地址，并支持远程地址的条件轮询（供预览监视）
+//! - 展开 include 时读取链接的 `details_file`（相对所在配置文件），并标记远程来源的链接
 
 use anyhow::{bail, Context, Result};
 use serde::{Deserialize, Serialize};
 use serde_yaml::{Mapping, Value};
-use std::collections::{BTreeSet, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
 use std::{
     fs,
     path::{Path, PathBuf},
@@ -32,6 +34,9 @@ pub(crate) struct Site {
     /// 主题目录（相对/绝对），例如 `themes/default`
     #[serde(default)]
     pub(crate) theme_dir: Option<String>,
+    /// 主题选项：按主题清单 `options` 中声明的默认值校验后，以 `theme` 注入所有模板
+    #[serde(default)]
+    pub(crate) theme_options: BTreeMap<String, Value>,
     /// 站点根路径（相对子路径），例如 `secretPath`，将输出到 `dist/secretPath/`
     /// 也支持多级 `a/b/c`。不允许 `.` 或 `..`。
     #[serde(default, alias = "root_path")]
@@ -69,6 +74,42 @@ pub(crate) struct Site {
     /// 可选：默认分类显示模式（未显式配置的分类使用），可取：standard|compact|list|text
     #[serde(default)]
     pub(crate) default_category_display: Option<String>,
+    /// 可选：压缩输出的 HTML 与 CSS/JS 资源（命令行 --minify 优先）
+    #[serde(default)]
+    pub(crate) minify: Option<bool>,
+    /// 可选：为 HTML/CSS/JS/SVG/JSON 生成 `.gz`/`.br` 预压缩文件（命令行 --precompress 优先）
+    #[serde(default)]
+    pub(crate) precompress: Option<bool>,
+    /// 可选：预压缩的最小文件大小（字节），默认 1024
+    #[serde(default)]
+    pub(crate) precompress_min_size: Option<u64>,
+    /// 可选：Service Worker 预缓存设置（包含/排除路径、总大小上限）
+    #[serde(default)]
+    pub(crate) precache: Option<PrecacheSettings>,
+    /// 可选：链接详情与简介的默认格式（markdown|html|text），缺省 html
+    #[serde(default)]
+    pub(crate) details_format: Option<DetailsFormat>,
+    /// 可选：详情/简介 HTML 清理设置（允许列表、远程来源默认 strict）
+    #[serde(default)]
+    pub(crate) sanitize: Option<SanitizeSettings>,
+    /// 可选：Markdown 自定义页面设置（目录、导航顺序）
+    #[serde(default)]
+    pub(crate) pages: Option<PagesSettings>,
+    /// 可选：内容安全策略（内联脚本/样式哈希、输出方式、额外来源）
+    #[serde(default)]
+    pub(crate) csp: Option<CspSettings>,
+    /// 可选：部署文件（`_headers`/`_redirects`、vercel.json、nginx 片段）设置
+    #[serde(default)]
+    pub(crate) deploy: Option<DeploySettings>,
+    /// 可选：短链接 `s/<slug>`（直接跳转目标地址，不经过中间页）
+    #[serde(default)]
+    pub(crate) short_links: Option<ShortLinkSettings>,
+    /// 可选：slug 锁文件设置（缺省启用，路径 `dove.lock`）
+    #[serde(default)]
+    pub(crate) slug_lock: Option<SlugLockSettings>,
+    /// 外网链接 slug 的生成方式（缺省 ascii）
+    #[serde(default)]
+    pub(crate) slug_strategy: SlugStrategy,
 }
 
 #[derive(Debug, Deserialize, Clone, Copy)]
@@ -104,12 +145,25 @@ pub(crate) struct Link {
     /// 简介（用于列表页显示）。兼容旧字段名 `desc`。
     #[serde(default, alias = "desc")]
     pub(crate) intro: String,
-    /// 详情（用于详情页，可写富文本 HTML）。未填写时默认回退为简介。
+    /// 详情（用于详情页，格式见 `details_format`）。未填写时默认回退为简介。
+    /// 也可用 `details_file` 引用单独的文件（加载配置时读入此字段）
     #[serde(default)]
     pub(crate) details: Option<String>,
+    /// 可选：详情与简介的格式（markdown|html|text），覆盖 site.details_format
+    #[serde(default)]
+    pub(crate) details_format: Option<DetailsFormat>,
+    /// 可选：信任该链接的详情 HTML，不做清理（远程来源的链接忽略此项）
+    #[serde(default)]
+    pub(crate) trusted: bool,
+    /// 内部字段：链接来自远程配置（加载时标记）
+    #[serde(default, rename = "_dove_remote")]
+    pub(crate) remote_source: bool,
     /// 可选：显式指定 slug（将用于外网详情页路径 go/<slug>/）
     #[serde(default)]
     pub(crate) slug: Option<String>,
+    /// 可选：短链接别名（启用 `site.short_links` 时生成 `s/<别名>`）
+    #[serde(default)]
+    pub(crate) aliases: Vec<String>,
     /// 可选：图标 URL（相对/绝对）
     #[serde(default)]
     pub(crate) icon: Option<String>,
@@ -136,6 +190,48 @@ pub(crate) struct Link {
     pub(crate) priority: Option<f32>,
 }
 
+/// 链接详情/简介格式
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum DetailsFormat {
+    /// Markdown：构建时渲染，代码块语法高亮；简介按行内 Markdown 渲染
+    Markdown,
+    /// 原样 HTML（历史行为）；简介为纯文本
+    #[default]
+    Html,
+    /// 纯文本：转义后按空行分段
+    Text,
+}
+
+/// HTML 清理档位
+#[derive(Debug, Deserialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum SanitizeProfile {
+    /// 常用排版、表格、图片与折叠块
+    Default,
+    /// 纯排版：不含图片与嵌入内容，`class` 仅用于代码高亮
+    Strict,
+}
+
+#[derive(Debug, Deserialize, Clone, Default)]
+pub(crate) struct SanitizeSettings {
+    /// 信任全部详情 HTML，完全关闭清理
+    #[serde(default)]
+    pub(crate) trusted: bool,
+    /// 本地配置中链接的清理档位，缺省 default
+    #[serde(default)]
+    pub(crate) profile: Option<SanitizeProfile>,
+    /// 远程配置（URL/Gist 及 URL include）中链接的清理档位，缺省 strict
+    #[serde(default)]
+    pub(crate) remote_profile: Option<SanitizeProfile>,
+    /// 额外允许的标签（仅作用于本地链接）
+    #[serde(default)]
+    pub(crate) allow_tags: Vec<String>,
+    /// 额外允许的属性：标签名（`*` 表示全部标签）-> 属性列表（仅作用于本地链接）
+    #[serde(default)]
+    pub(crate) allow_attributes: BTreeMap<String, Vec<String>>,
+}
+
 #[derive(Debug, Deserialize, Clone, Copy)]
 #[serde(rename_all = "lowercase")]
 pub(crate) enum RiskLevel {
@@ -212,6 +308,138 @@ pub(crate) struct SitemapSettings {
     pub(crate) lastmod: Option<String>,
 }
 
+#[derive(Debug, Deserialize, Clone, Default)]
+pub(crate) struct PrecacheSettings {
+    /// 仅预缓存匹配的路径（相对站点根的 glob，如 `go/**`）；缺省为全部
+    #[serde(default)]
+    pub(crate) include: Option<Vec<String>>,
+    /// 排除匹配的路径（glob，如 `assets/*.png`）
+    #[serde(default)]
+    pub(crate) exclude: Option<Vec<String>>,
+    /// 预缓存总大小上限（KB）；超出部分按优先级（页面 > CSS/JS > 其他）截断
+    #[serde(default)]
+    pub(crate) max_total_kb: Option<u64>,
+}
+
+#[derive(Debug, Deserialize, Clone, Default)]
+pub(crate) struct PagesSettings {
+    /// 页面目录（相对/绝对），默认 `pages`
+    #[serde(default)]
+    pub(crate) dir: Option<String>,
+    /// 导航中展示的页面 slug 及顺序；缺省按页面 `order` 展示全部 `nav: true` 的页面
+    #[serde(default)]
+    pub(crate) nav: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize, Clone, Default)]
+pub(crate) struct CspSettings {
+    /// 输出方式：meta（逐页 `<meta>`，缺省）、headers（`_headers` 文件）或 both
+    #[serde(default)]
+    pub(crate) mode: CspMode,
+    /// 是否按 `google_analytics_id` / `baidu_tongji_id` 自动放行统计域名，缺省是
+    #[serde(default)]
+    pub(crate) analytics: Option<bool>,
+    /// 按指令追加来源，如 `script-src: ["https://cdn.example.com"]`；未知指令将新增
+    #[serde(default)]
+    pub(crate) extra_sources: BTreeMap<String, Vec<String>>,
+}
+
+/// CSP 输出方式
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum CspMode {
+    #[default]
+    Meta,
+    Headers,
+    Both,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub(crate) struct DeploySettings {
+    /// 生成的部署文件：cloudflare（`_headers` + `_redirects`，Netlify 通用）、vercel、nginx；缺省 cloudflare
+    #[serde(default = "default_deploy_targets")]
+    pub(crate) targets: Vec<DeployTarget>,
+    /// 带指纹的资源长期缓存（immutable），`sw.js` 不缓存；缺省是
+    #[serde(default)]
+    pub(crate) cache_assets: Option<bool>,
+    /// 常用安全响应头（nosniff、Referrer-Policy、X-Frame-Options、Permissions-Policy）；缺省是
+    #[serde(default)]
+    pub(crate) security_headers: Option<bool>,
+    /// 追加 Strict-Transport-Security（仅在全站 HTTPS 时开启）
+    #[serde(default)]
+    pub(crate) hsts: bool,
+    /// 追加到全站的自定义响应头
+    #[serde(default)]
+    pub(crate) headers: BTreeMap<String, String>,
+    /// 未生成中间页的链接：`go/<slug>/` 301 到目标地址；缺省是
+    #[serde(default)]
+    pub(crate) redirect_go: Option<bool>,
+}
+
+fn default_deploy_targets() -> Vec<DeployTarget> {
+    vec![DeployTarget::Cloudflare]
+}
+
+/// 部署平台
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum DeployTarget {
+    #[serde(alias = "netlify")]
+    Cloudflare,
+    Vercel,
+    Nginx,
+}
+
+#[derive(Debug, Deserialize, Clone, Default)]
+pub(crate) struct ShortLinkSettings {
+    /// 输出方式：html（`s/<slug>/index.html` 跳转页，缺省）、rules（部署平台重定向规则）或 both
+    #[serde(default)]
+    pub(crate) mode: ShortLinkMode,
+    /// 短链接目录，默认 `s`
+    #[serde(default)]
+    pub(crate) dir: Option<String>,
+}
+
+/// 短链接输出方式
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum ShortLinkMode {
+    #[default]
+    Html,
+    Rules,
+    Both,
+}
+
+#[derive(Debug, Deserialize, Clone, Default)]
+pub(crate) struct SlugLockSettings {
+    /// 是否读写锁文件，缺省是
+    #[serde(default)]
+    pub(crate) enabled: Option<bool>,
+    /// 锁文件路径（相对本地配置所在目录；远程配置时相对输出目录的上级），默认 `dove.lock`
+    #[serde(default)]
+    pub(crate) path: Option<String>,
+    /// 为停用的旧 slug 生成到当前地址的重定向，缺省是
+    #[serde(default)]
+    pub(crate) redirect_retired: Option<bool>,
+}
+
+/// 外网链接 slug 的生成方式（记录在 slug 锁文件中）
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum SlugStrategy {
+    /// 仅保留 ASCII 字母与数字
+    #[default]
+    Ascii,
+    /// transliterate 的别名（转写表中的汉字即为不带声调的拼音）
+    Pinyin,
+    /// 各种文字统一转写为 ASCII（汉字为拼音，并去除重音等）
+    Transliterate,
+    /// 保留 Unicode 字母与数字，链接地址中按 UTF-8 百分号编码
+    Unicode,
+    /// 由链接 URL 计算的短哈希
+    Hash,
+}
+
 /// 配置来源（用于打印和调试）
 #[derive(Debug, Clone)]
 pub(crate) enum ConfigSource {
@@ -232,6 +460,59 @@ pub(crate) enum ConfigSource {
 pub(crate) struct LoadedConfig {
     pub(crate) text: String,
     pub(crate) source: ConfigSource,
+    /// 参与本次加载的本地文件（主配置与全部 include）
+    pub(crate) files: Vec<PathBuf>,
+    /// 参与本次加载的远程地址（主配置与 URL include；Gist 为其 API 地址）
+    pub(crate) urls: Vec<String>,
+}
+
+impl LoadedConfig {
+    fn new(expanded: Expanded, source: ConfigSource, main: ConfigDependency) -> Self {
+        let mut files = Vec::new();
+        let mut urls = Vec::new();
+        for dep in std::iter::once(main).chain(expanded.dependencies) {
+            match dep {
+                ConfigDependency::File(p) => files.push(p),
+                ConfigDependency::Url(u) => urls.push(u),
+            }
+        }
+        Self {
+            text: expanded.text,
+            source,
+            files,
+            urls,
+        }
+    }
+}
+
+/// 配置依赖项：本地文件或远程地址
+#[derive(Debug, Clone)]
+enum ConfigDependency {
+    File(PathBuf),
+    #[cfg_attr(not(feature = "remote"), allow(dead_code))]
+    Url(String),
+}
+
+/// 展开 include 后的配置文本及其引用的全部片段
+struct Expanded {
+    text: String,
+    dependencies: Vec<ConfigDependency>,
+}
+
+impl ConfigSource {
+    /// 本地配置文件所在目录（远程来源为 None）
+    pub(crate) fn local_dir(&self) -> Option<PathBuf> {
+        match self {
+            ConfigSource::LocalExplicit(p) | ConfigSource::LocalAuto(p) => Some(
+                Path::new(p)
+                    .parent()
+                    .unwrap_or(Path::new("."))
+                    .to_path_buf(),
+            ),
+            #[cfg(feature = "remote")]
+            _ => None,
+        }
+    }
 }
 
 /// 人类可读的来源描述
@@ -296,23 +577,25 @@ pub(crate) fn load_config(
     if let Some(path) = _resolve_explicit_config_path(input_path) {
         let raw = fs::read_to_string(&path)
             .with_context(|| format!("读取配置失败: {}", path.display()))?;
-        let text = expand_includes_text(&raw, Some(&path), None, token, auth_scheme)
+        let expanded = expand_includes_text(&raw, Some(&path), None, token, auth_scheme)
             .with_context(|| format!("展开 include 失败: {}", path.display()))?;
-        return Ok(LoadedConfig {
-            text,
-            source: ConfigSource::LocalExplicit(path.display().to_string()),
-        });
+        return Ok(LoadedConfig::new(
+            expanded,
+            ConfigSource::LocalExplicit(path.display().to_string()),
+            ConfigDependency::File(path),
+        ));
     }
     // 2) URL
     if let Some(url) = input_url {
         let raw = http_get_text(url, token, auth_scheme)
             .with_context(|| format!("下载配置失败: {}", url))?;
-        let text = expand_includes_text(&raw, None, Some(url), token, auth_scheme)
+        let expanded = expand_includes_text(&raw, None, Some(url), token, auth_scheme)
             .with_context(|| format!("展开 include 失败: {}", url))?;
-        return Ok(LoadedConfig {
-            text,
-            source: ConfigSource::Url(url.to_string()),
-        });
+        return Ok(LoadedConfig::new(
+            expanded,
+            ConfigSource::Url(url.to_string()),
+            ConfigDependency::Url(url.to_string()),
+        ));
     }
     // 3) Gist by ID（若提供则优先于本地自动发现）
     if let Some(id) = gist_id {
@@ -324,7 +607,7 @@ pub(crate) fn load_config(
                 chosen.as_deref().unwrap_or("<auto>")
             )
         })?;
-        let text = expand_includes_text(&raw, None, Some(&raw_url), token, auth_scheme)
+        let expanded = expand_includes_text(&raw, None, Some(&raw_url), token, auth_scheme)
             .with_context(|| {
                 format!(
                     "展开 include 失败: Gist {} 文件 {}",
@@ -332,25 +615,28 @@ pub(crate) fn load_config(
                     chosen.as_deref().unwrap_or("<auto>")
                 )
             })?;
-        return Ok(LoadedConfig {
-            text,
-            source: ConfigSource::Gist {
+        // raw_url 固定到某个修订版本，需轮询 Gist API 才能发现更新
+        return Ok(LoadedConfig::new(
+            expanded,
+            ConfigSource::Gist {
                 id: id.to_string(),
                 file: chosen,
                 raw_url,
             },
-        });
+            ConfigDependency::Url(gist_api_url(id)),
+        ));
     }
     // 4) 本地自动查找
     if let Some(path) = _resolve_local_config_path(None) {
         let raw = fs::read_to_string(&path)
             .with_context(|| format!("读取配置失败: {}", path.display()))?;
-        let text = expand_includes_text(&raw, Some(&path), None, token, auth_scheme)
+        let expanded = expand_includes_text(&raw, Some(&path), None, token, auth_scheme)
             .with_context(|| format!("展开 include 失败: {}", path.display()))?;
-        return Ok(LoadedConfig {
-            text,
-            source: ConfigSource::LocalAuto(path.display().to_string()),
-        });
+        return Ok(LoadedConfig::new(
+            expanded,
+            ConfigSource::LocalAuto(path.display().to_string()),
+            ConfigDependency::File(path),
+        ));
     }
     bail!("未找到配置：请提供 --input 或 --input-url，或设置 DOVE_INPUT/DOVE_INPUT_URL/DOVE_GIST_ID，或在当前目录放置 dove.yaml");
 }
@@ -367,28 +653,37 @@ pub(crate) fn load_config(
     if let Some(path) = _resolve_explicit_config_path(input_path) {
         let raw = fs::read_to_string(&path)
             .with_context(|| format!("读取配置失败: {}", path.display()))?;
-        let text = expand_includes_text(&raw, Some(&path), None)
+        let expanded = expand_includes_text(&raw, Some(&path), None)
             .with_context(|| format!("展开 include 失败: {}", path.display()))?;
-        return Ok(LoadedConfig {
-            text,
-            source: ConfigSource::LocalExplicit(path.display().to_string()),
-        });
+        return Ok(LoadedConfig::new(
+            expanded,
+            ConfigSource::LocalExplicit(path.display().to_string()),
+            ConfigDependency::File(path),
+        ));
     }
     if let Some(path) = _resolve_local_config_path(None) {
         let raw = fs::read_to_string(&path)
             .with_context(|| format!("读取配置失败: {}", path.display()))?;
-        let text = expand_includes_text(&raw, Some(&path), None)
+        let expanded = expand_includes_text(&raw, Some(&path), None)
             .with_context(|| format!("展开 include 失败: {}", path.display()))?;
-        return Ok(LoadedConfig {
-            text,
-            source: ConfigSource::LocalAuto(path.display().to_string()),
-        });
+        return Ok(LoadedConfig::new(
+            expanded,
+            ConfigSource::LocalAuto(path.display().to_string()),
+            ConfigDependency::File(path),
+        ));
     }
     bail!("未找到本地配置：在禁用 remote 功能时，无法使用 URL/Gist。请启用 feature `remote` 或在当前目录提供 dove.yaml");
 }
 
 #[cfg(feature = "remote")]
 fn http_get_text(url: &str, token: Option<&str>, auth_scheme: Option<&str>) -> Result<String> {
+    let resp = ensure_success(authorized_get(url, token, auth_scheme).call(), url)?;
+    resp.into_string()
+        .with_context(|| format!("读取响应文本失败: {}", url))
+}
+
+#[cfg(feature = "remote")]
+fn authorized_get(url: &str, token: Option<&str>, auth_scheme: Option<&str>) -> ureq::Request {
     let mut req = ureq::get(url).set("User-Agent", "dove/0.1");
     if let Some(t) = token {
         let scheme = auth_scheme
@@ -397,9 +692,51 @@ fn http_get_text(url: &str, token: Option<&str>, auth_scheme: Option<&str>) -> R
             .unwrap_or("token");
         req = req.set("Authorization", &format!("{} {}", scheme, t));
     }
-    let resp = ensure_success(req.call(), url)?;
-    resp.into_string()
-        .with_context(|| format!("读取响应文本失败: {}", url))
+    req
+}
+
+/// 远程配置的条件请求状态：优先使用 ETag/Last-Modified，最终以内容哈希判断是否变化
+#[cfg(feature = "remote")]
+#[derive(Debug, Clone, Default)]
+pub(crate) struct RemoteValidator {
+    etag: Option<String>,
+    last_modified: Option<String>,
+    content_hash: Option<u64>,
+}
+
+#[cfg(feature = "remote")]
+impl RemoteValidator {
+    /// 发起条件请求，返回内容是否相对上次发生变化（首次调用仅记录状态）
+    pub(crate) fn poll(
+        &mut self,
+        url: &str,
+        token: Option<&str>,
+        auth_scheme: Option<&str>,
+    ) -> Result<bool> {
+        let mut req = authorized_get(url, token, auth_scheme);
+        if let Some(etag) = self.etag.as_deref() {
+            req = req.set("If-None-Match", etag);
+        }
+        if let Some(lm) = self.last_modified.as_deref() {
+            req = req.set("If-Modified-Since", lm);
+        }
+        let resp = ensure_success(req.call(), url)?;
+        if resp.status() == 304 {
+            return Ok(false);
+        }
+        let etag = resp.header("ETag").map(str::to_string);
+        let last_modified = resp.header("Last-Modified").map(str::to_string);
+        let body = resp
+            .into_string()
+            .with_context(|| format!("读取响应文本失败: {}", url))?;
+        let hash = crate::utils::fnv1a64(body.as_bytes());
+        // 服务器可能忽略条件请求，内容未变时不算变化
+        let changed = self.content_hash.is_some_and(|h| h != hash);
+        self.etag = etag;
+        self.last_modified = last_modified;
+        self.content_hash = Some(hash);
+        Ok(changed)
+    }
 }
 
 #[cfg(feature = "remote")]
@@ -410,6 +747,11 @@ fn ensure_success(resp: Result<Response, ureq::Error>, url: &str) -> Result<Resp
     }
 }
 
+#[cfg(feature = "remote")]
+fn gist_api_url(id: &str) -> String {
+    format!("https://api.github.com/gists/{}", id)
+}
+
 #[cfg(feature = "remote")]
 fn gist_resolve_raw_url(
     id: &str,
@@ -417,7 +759,7 @@ fn gist_resolve_raw_url(
     token: Option<&str>,
     auth_scheme: Option<&str>,
 ) -> Result<(String, Option<String>)> {
-    let api = format!("https://api.github.com/gists/{}", id);
+    let api = gist_api_url(id);
     let mut req = ureq::get(&api)
         .set("User-Agent", "dove/0.1")
         .set("Accept", "application/vnd.github+json");
@@ -498,10 +840,8 @@ fn mapping_remove_includes(m: &mut Mapping) -> Option<Vec<String>> {
         let k = Value::String(key.to_string());
         if let Some(v) = m.remove(&k) {
             match v {
-                Value::String(s) => {
-                    if !s.trim().is_empty() {
-                        includes.push(s);
-                    }
+                Value::String(s) if !s.trim().is_empty() => {
+                    includes.push(s);
                 }
                 Value::Sequence(arr) => {
                     for item in arr.into_iter() {
@@ -551,6 +891,16 @@ fn expand_includes_value(
     #[cfg(feature = "remote")] token: Option<&str>,
     #[cfg(feature = "remote")] auth_scheme: Option<&str>,
 ) -> Result<Value> {
+    // 先读入本文件中链接的 details_file（路径相对本文件），并标记远程来源
+    prepare_links(
+        &mut root,
+        base,
+        visited,
+        #[cfg(feature = "remote")]
+        token,
+        #[cfg(feature = "remote")]
+        auth_scheme,
+    )?;
     // 仅在映射的最外层处理 include；并允许递归 include
     if let Value::Mapping(ref mut m) = root {
         let includes = mapping_remove_includes(m).unwrap_or_default();
@@ -625,6 +975,7 @@ fn expand_includes_value(
                             .with_context(|| format!("下载 include 失败: {}", target))?;
                         let mut v: Value = serde_yaml::from_str(&text)
                             .with_context(|| format!("解析 YAML 失败: {}", target))?;
+                        strip_remote_sanitize(&mut v, "远程 include", &target);
                         let new_base = IncludeBase::UrlBase(value_dir_of_url(&target));
                         v = expand_includes_value(v, &new_base, visited, token, auth_scheme)?;
                         if let Value::Sequence(seq) = v {
@@ -649,13 +1000,112 @@ fn expand_includes_value(
     Ok(root)
 }
 
+/// 远程配置（主配置与 include 片段）不得修改清理策略：移除 `site.sanitize` 并告警。
+/// 主配置来自远程时即按全部默认值清理（远程来源链接为 `strict`）
+#[cfg(feature = "remote")]
+fn strip_remote_sanitize(v: &mut Value, kind: &str, origin: &str) {
+    if let Some(Value::Mapping(site)) = v.get_mut("site") {
+        if site.remove("sanitize").is_some() {
+            eprintln!("警告: {}中的 site.sanitize 已忽略: {}", kind, origin);
+        }
+    }
+}
+
+/// 处理 `groups[].links[]`：
+/// - 将 `details_file` 读入 `details`；未设置 `details_format` 时按扩展名推断
+///   （`.md`/`.markdown` -> markdown，`.html`/`.htm` -> html，`.txt` -> text）。
+///   读取的文件记入 `visited`，从而出现在配置依赖中（预览会监视）
+/// - 远程来源（URL 基准）的链接标记 `_dove_remote`，构建时按 `site.sanitize.remote_profile` 清理
+fn prepare_links(
+    root: &mut Value,
+    base: &IncludeBase,
+    visited: &mut HashSet<String>,
+    #[cfg(feature = "remote")] token: Option<&str>,
+    #[cfg(feature = "remote")] auth_scheme: Option<&str>,
+) -> Result<()> {
+    // include 根为序列时即为 groups 片段
+    let groups = match root {
+        Value::Mapping(m) => match m.get_mut("groups") {
+            Some(Value::Sequence(seq)) => seq,
+            _ => return Ok(()),
+        },
+        Value::Sequence(seq) => seq,
+        _ => return Ok(()),
+    };
+    let links = groups
+        .iter_mut()
+        .filter_map(|g| match g.get_mut("links") {
+            Some(Value::Sequence(seq)) => Some(seq),
+            _ => None,
+        })
+        .flatten()
+        .filter_map(Value::as_mapping_mut);
+    for link in links {
+        if matches!(base, IncludeBase::LocalDir(_)) {
+            link.remove("_dove_remote");
+        } else {
+            link.insert(Value::from("_dove_remote"), Value::Bool(true));
+        }
+        let Some(Value::String(file)) = link.remove("details_file") else {
+            continue;
+        };
+        let name = link
+            .get("name")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string();
+        if link.get("details").is_some_and(|v| !v.is_null()) {
+            bail!("链接 {} 同时设置了 details 与 details_file", name);
+        }
+        let file = file.trim();
+        let text = match base {
+            IncludeBase::LocalDir(dir) => {
+                let p = dir.join(file);
+                let text = fs::read_to_string(&p).with_context(|| {
+                    format!("读取链接 {} 的 details_file 失败: {}", name, p.display())
+                })?;
+                let abs = p.canonicalize().unwrap_or(p);
+                visited.insert(format!("local::{}", abs.display()));
+                text
+            }
+            #[cfg(feature = "remote")]
+            IncludeBase::UrlBase(base_url) => {
+                let target = join_url(base_url, file);
+                let text = http_get_text(&target, token, auth_scheme).with_context(|| {
+                    format!("下载链接 {} 的 details_file 失败: {}", name, target)
+                })?;
+                visited.insert(format!("url::{}", target));
+                text
+            }
+        };
+        link.insert(Value::from("details"), Value::String(text));
+        if link.get("details_format").is_none() {
+            let ext = Path::new(file)
+                .extension()
+                .and_then(|e| e.to_str())
+                .unwrap_or_default()
+                .to_ascii_lowercase();
+            let format = match ext.as_str() {
+                "md" | "markdown" => Some("markdown"),
+                "html" | "htm" => Some("html"),
+                "txt" => Some("text"),
+                _ => None,
+            };
+            if let Some(f) = format {
+                link.insert(Value::from("details_format"), Value::from(f));
+            }
+        }
+    }
+    Ok(())
+}
+
 fn expand_includes_text(
     text: &str,
     base_path: Option<&Path>,
     #[allow(unused_variables)] base_url: Option<&str>,
     #[cfg(feature = "remote")] token: Option<&str>,
     #[cfg(feature = "remote")] auth_scheme: Option<&str>,
-) -> Result<String> {
+) -> Result<Expanded> {
     let mut v: Value = serde_yaml::from_str(text)?;
     let mut visited: HashSet<String> = HashSet::new();
     let base = if let Some(p) = base_path {
@@ -664,6 +1114,7 @@ fn expand_includes_text(
         #[cfg(feature = "remote")]
         {
             let url = base_url.unwrap_or("");
+            strip_remote_sanitize(&mut v, "远程配置", url);
             IncludeBase::UrlBase(value_dir_of_url(url))
         }
         #[cfg(not(feature = "remote"))]
@@ -681,6 +1132,20 @@ fn expand_includes_text(
         #[cfg(feature = "remote")]
         auth_scheme,
     )?;
-    let s = serde_yaml::to_string(&v)?;
-    Ok(s)
+    let text = serde_yaml::to_string(&v)?;
+    // visited 记录了全部已展开的片段（`local::<路径>` / `url::<地址>`）
+    let mut keys: Vec<String> = visited.into_iter().collect();
+    keys.sort();
+    let dependencies = keys
+        .into_iter()
+        .filter_map(|key| {
+            if let Some(p) = key.strip_prefix("local::") {
+                Some(ConfigDependency::File(PathBuf::from(p)))
+            } else {
+                key.strip_prefix("url::")
+                    .map(|u| ConfigDependency::Url(u.to_string()))
+            }
+        })
+        .collect();
+    Ok(Expanded { text, dependencies })
 }
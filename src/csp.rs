@@ -0,0 +1,338 @@
+//! 内容安全策略（CSP）模块：
+//! - 构建后扫描输出的 HTML，计算内联 `<script>`/`<style>` 的 SHA-256
+//! - 按页面写入 `<meta http-equiv="Content-Security-Policy">`，和/或生成全站策略供部署文件写出
+//! - 按统计配置自动加入 Google Analytics / 百度统计所需来源，并支持按指令追加来源
+//! - 预览注入热刷新脚本时，同步把该脚本的哈希加入页面策略
+
+use anyhow::{Context, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::BTreeSet,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::config::{CspMode, CspSettings, Site};
+
+/// CSP 响应头的建议上限（字节），超出时提示改用 meta
+const HEADER_SOFT_LIMIT: usize = 4096;
+
+const GA_SCRIPT: &[&str] = &["https://www.googletagmanager.com"];
+const GA_CONNECT: &[&str] = &[
+    "https://*.google-analytics.com",
+    "https://*.analytics.google.com",
+    "https://*.googletagmanager.com",
+];
+const BAIDU_SCRIPT: &[&str] = &["https://hm.baidu.com"];
+const BAIDU_CONNECT: &[&str] = &["https://hm.baidu.com"];
+
+/// 一条策略：按指令顺序保存来源
+#[derive(Debug, Clone)]
+struct Policy {
+    directives: Vec<(String, Vec<String>)>,
+}
+
+impl Policy {
+    /// 站点基础策略（不含内联哈希）
+    fn base(settings: &CspSettings, site: &Site) -> Self {
+        let mut policy = Self {
+            directives: vec![
+                ("default-src".into(), vec!["'self'".into()]),
+                ("script-src".into(), vec!["'self'".into()]),
+                ("style-src".into(), vec!["'self'".into()]),
+                // 图标与背景图来自任意站点
+                (
+                    "img-src".into(),
+                    vec!["'self'".into(), "data:".into(), "https:".into()],
+                ),
+                ("font-src".into(), vec!["'self'".into(), "data:".into()]),
+                ("connect-src".into(), vec!["'self'".into()]),
+                ("object-src".into(), vec!["'none'".into()]),
+                ("base-uri".into(), vec!["'self'".into()]),
+                ("form-action".into(), vec!["'self'".into()]),
+            ],
+        };
+        if settings.analytics.unwrap_or(true) {
+            if has_value(site.google_analytics_id.as_deref()) {
+                policy.add("script-src", GA_SCRIPT.iter().copied());
+                policy.add("connect-src", GA_CONNECT.iter().copied());
+            }
+            if has_value(site.baidu_tongji_id.as_deref()) {
+                policy.add("script-src", BAIDU_SCRIPT.iter().copied());
+                policy.add("connect-src", BAIDU_CONNECT.iter().copied());
+            }
+        }
+        for (directive, sources) in &settings.extra_sources {
+            policy.add(
+                &directive.trim().to_ascii_lowercase(),
+                sources.iter().map(String::as_str),
+            );
+        }
+        policy
+    }
+
+    /// 追加来源（去重）；指令不存在时新增
+    fn add<'a>(&mut self, directive: &str, sources: impl IntoIterator<Item = &'a str>) {
+        let idx = match self.directives.iter().position(|(d, _)| d == directive) {
+            Some(i) => i,
+            None => {
+                self.directives.push((directive.to_string(), Vec::new()));
+                self.directives.len() - 1
+            }
+        };
+        let list = &mut self.directives[idx].1;
+        for s in sources {
+            let s = s.trim();
+            if !s.is_empty() && !list.iter().any(|x| x == s) {
+                list.push(s.to_string());
+            }
+        }
+    }
+
+    fn with_inline(&self, inline: &InlineContent) -> Self {
+        let mut policy = self.clone();
+        policy.add("script-src", inline.scripts.iter().map(String::as_str));
+        policy.add("style-src", inline.styles.iter().map(String::as_str));
+        if inline.style_attrs {
+            // style 属性无法按哈希放行（模板中的 `style="..."` 与脚本设置的样式）
+            policy.add("style-src-attr", ["'unsafe-inline'"]);
+        }
+        policy
+    }
+
+    fn render(&self) -> String {
+        self.directives
+            .iter()
+            .map(|(d, s)| {
+                if s.is_empty() {
+                    d.clone()
+                } else {
+                    format!("{} {}", d, s.join(" "))
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+}
+
+/// 页面中的内联内容
+#[derive(Debug, Default)]
+struct InlineContent {
+    scripts: BTreeSet<String>,
+    styles: BTreeSet<String>,
+    style_attrs: bool,
+}
+
+impl InlineContent {
+    fn merge(&mut self, other: InlineContent) {
+        self.scripts.extend(other.scripts);
+        self.styles.extend(other.styles);
+        self.style_attrs |= other.style_attrs;
+    }
+}
+
+/// 为站点输出生成 CSP：meta 模式逐页写入；headers 模式返回全站策略，由部署文件写出
+pub(crate) fn apply(
+    site_dir: &Path,
+    settings: &CspSettings,
+    site: &Site,
+) -> Result<Option<String>> {
+    let base = Policy::base(settings, site);
+    let mut files = Vec::new();
+    collect_html(site_dir, &mut files)?;
+    files.sort();
+
+    let write_meta = matches!(settings.mode, CspMode::Meta | CspMode::Both);
+    let mut all = InlineContent::default();
+    let mut skipped = 0;
+    for path in &files {
+        let html = fs::read_to_string(path)
+            .with_context(|| format!("读取页面失败: {}", path.display()))?;
+        let inline = scan_inline(&html);
+        if write_meta {
+            match insert_meta(&html, &base.with_inline(&inline).render()) {
+                Some(out) => fs::write(path, out)
+                    .with_context(|| format!("写入 CSP 失败: {}", path.display()))?,
+                None => skipped += 1,
+            }
+        }
+        all.merge(inline);
+    }
+    if skipped > 0 {
+        eprintln!(
+            "警告: {} 个页面已自带 CSP meta 或缺少 <head>，未写入 CSP",
+            skipped
+        );
+    }
+
+    let header = if matches!(settings.mode, CspMode::Headers | CspMode::Both) {
+        let mut policy = base.with_inline(&all);
+        // frame-ancestors 仅在响应头中生效
+        policy.add("frame-ancestors", ["'self'"]);
+        let value = policy.render();
+        if value.len() > HEADER_SOFT_LIMIT {
+            eprintln!(
+                "警告: CSP 响应头长度 {} 字节（内联哈希 {} 个），部分托管平台可能截断；可改用 site.csp.mode: meta",
+                value.len(),
+                all.scripts.len() + all.styles.len()
+            );
+        }
+        Some(value)
+    } else {
+        None
+    };
+    println!(
+        "🛡️ CSP: {} 个页面，内联脚本哈希 {} 个、样式哈希 {} 个",
+        files.len(),
+        all.scripts.len(),
+        all.styles.len()
+    );
+    Ok(header)
+}
+
+/// 预览：把注入的脚本（完整 `<script>` 标签）哈希加入页面已有的 CSP meta
+pub(crate) fn allow_preview_script(html: String, script_tag: &str) -> String {
+    let body = script_tag
+        .trim()
+        .strip_prefix("<script>")
+        .and_then(|s| s.strip_suffix("</script>"))
+        .unwrap_or(script_tag);
+    let Some((start, end)) = find_meta_content(&html) else {
+        return html;
+    };
+    let mut policy = Policy {
+        directives: html[start..end]
+            .split(';')
+            .filter_map(|d| {
+                let mut parts = d.split_whitespace();
+                let name = parts.next()?.to_string();
+                Some((name, parts.map(str::to_string).collect()))
+            })
+            .collect(),
+    };
+    policy.add("script-src", [hash(body).as_str()]);
+    // 错误浮层通过 style 属性设置样式
+    policy.add("style-src-attr", ["'unsafe-inline'"]);
+    let mut out = html;
+    out.replace_range(start..end, &policy.render());
+    out
+}
+
+fn has_value(s: Option<&str>) -> bool {
+    s.is_some_and(|v| !v.trim().is_empty())
+}
+
+fn hash(content: &str) -> String {
+    format!(
+        "'sha256-{}'",
+        STANDARD.encode(Sha256::digest(content.as_bytes()))
+    )
+}
+
+fn collect_html(dir: &Path, out: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in fs::read_dir(dir).with_context(|| format!("读取目录失败: {}", dir.display()))?
+    {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_html(&path, out)?;
+        } else if path.extension().and_then(|e| e.to_str()) == Some("html") {
+            out.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// 扫描内联 `<script>`（无 `src`）与 `<style>` 的内容哈希，以及是否存在 style 属性
+fn scan_inline(html: &str) -> InlineContent {
+    let lower = html.to_ascii_lowercase();
+    let mut inline = InlineContent::default();
+    let mut pos = 0;
+    while let Some(off) = lower[pos..].find('<') {
+        let start = pos + off;
+        let Some(tag_end) = lower[start..].find('>').map(|e| start + e) else {
+            break;
+        };
+        let tag = &lower[start..tag_end];
+        pos = tag_end + 1;
+        let name = tag[1..]
+            .split(|c: char| c.is_ascii_whitespace() || c == '/')
+            .next()
+            .unwrap_or("");
+        if tag.contains(" style=") {
+            inline.style_attrs = true;
+        }
+        if name != "script" && name != "style" {
+            continue;
+        }
+        let close = format!("</{}", name);
+        let Some(body_end) = lower[pos..].find(&close).map(|e| pos + e) else {
+            break;
+        };
+        let body = &html[pos..body_end];
+        if name == "script" {
+            if !has_attr(tag, "src") && is_executable(tag) {
+                inline.scripts.insert(hash(body));
+            }
+        } else {
+            inline.styles.insert(hash(body));
+        }
+        pos = body_end;
+    }
+    inline
+}
+
+fn has_attr(tag: &str, attr: &str) -> bool {
+    tag.split(|c: char| c.is_ascii_whitespace())
+        .skip(1)
+        .any(|a| a == attr || a.starts_with(&format!("{}=", attr)))
+}
+
+/// 数据块（如 `application/ld+json`）不执行，也不受 CSP 约束
+fn is_executable(tag: &str) -> bool {
+    let Some(idx) = tag.find(" type=") else {
+        return true;
+    };
+    let value = tag[idx + " type=".len()..]
+        .trim_start_matches(['"', '\''])
+        .split(['"', '\'', ' '])
+        .next()
+        .unwrap_or("");
+    matches!(
+        value,
+        "" | "module" | "text/javascript" | "application/javascript"
+    )
+}
+
+/// 已有 CSP meta 的 content 值范围
+fn find_meta_content(html: &str) -> Option<(usize, usize)> {
+    let lower = html.to_ascii_lowercase();
+    let idx = lower.find("http-equiv=\"content-security-policy\"")?;
+    let tag_start = lower[..idx].rfind('<')?;
+    let tag_end = tag_start + lower[tag_start..].find('>')?;
+    let content = tag_start + lower[tag_start..tag_end].find("content=\"")? + "content=\"".len();
+    let end = content + html[content..tag_end].find('"')?;
+    Some((content, end))
+}
+
+/// 在 `<meta charset>` 之后（没有则在 `<head>` 之后）插入 CSP meta；已有 CSP 或缺少 `<head>` 时返回 None
+fn insert_meta(html: &str, policy: &str) -> Option<String> {
+    if find_meta_content(html).is_some() {
+        return None;
+    }
+    let lower = html.to_ascii_lowercase();
+    let anchor = match lower.find("<meta charset") {
+        Some(i) => i,
+        None => lower.find("<head")?,
+    };
+    let at = anchor + lower[anchor..].find('>')? + 1;
+    let meta = format!(
+        "<meta http-equiv=\"Content-Security-Policy\" content=\"{}\">",
+        policy
+    );
+    let mut out = String::with_capacity(html.len() + meta.len());
+    out.push_str(&html[..at]);
+    out.push_str(&meta);
+    out.push_str(&html[at..]);
+    Some(out)
+}
@@ -0,0 +1,383 @@
+//! 部署文件模块：
+//! - 按 `site.deploy.targets` 生成 `_headers`/`_redirects`（Cloudflare Pages、Netlify）、`vercel.json` 与 nginx 配置片段
+//! - 响应头：带指纹资源长期缓存、常用安全头、自定义头与 CSP（`site.csp.mode: headers|both`）
+//! - 重定向：未生成中间页的 `go/<slug>/` 301 到目标地址；不支持规则的托管可写出跳转页
+//! - 静态目录中已有的同名文件保留其规则，生成内容追加在后
+
+use anyhow::{bail, Context, Result};
+use serde::Serialize;
+use std::{fs, path::Path};
+
+use crate::{
+    config::{DeploySettings, DeployTarget},
+    fingerprint::AssetManifest,
+    slugs::encode_path,
+};
+
+/// nginx 配置片段文件名（在 `server { }` 中 include）
+const NGINX_FILE: &str = "dove.nginx.conf";
+const IMMUTABLE: &str = "public, max-age=31536000, immutable";
+
+/// 一条重定向：`from` 相对站点根（如 `go/github/`），`to` 为目标地址或相对站点根的路径
+#[derive(Debug, Clone)]
+pub(crate) struct Redirect {
+    pub(crate) from: String,
+    pub(crate) to: String,
+}
+
+impl Redirect {
+    /// 目标是否为站内路径：带协议（`https:`、`mailto:` 等）或以 `//` 开头的均为外部地址
+    fn is_internal(&self) -> bool {
+        !(self.to.starts_with("//") || has_scheme(&self.to))
+    }
+}
+
+/// 待写出的规则（路径均已带站点前缀）
+struct Rules {
+    /// 全站响应头
+    global: Vec<(String, String)>,
+    /// 按路径的响应头
+    paths: Vec<(String, Vec<(String, String)>)>,
+    redirects: Vec<(String, String)>,
+}
+
+/// 生成部署文件写入 `root`（部署根目录）；`site_dir` 中来自静态目录的同名文件合并后移除（若二者不同）
+///
+/// 未配置 `site.deploy` 时，仅在有 CSP 响应头或重定向时写出 `_headers`/`_redirects`。
+pub(crate) fn write_deploy_files(
+    root: &Path,
+    site_dir: &Path,
+    base_path: Option<&str>,
+    settings: Option<&DeploySettings>,
+    assets: &AssetManifest,
+    csp: Option<&str>,
+    redirects: &[Redirect],
+) -> Result<()> {
+    let prefix = match base_path.map(|b| b.trim_matches('/')) {
+        Some(b) if !b.is_empty() => format!("/{}/", b),
+        _ => "/".to_string(),
+    };
+    let rules = collect_rules(&prefix, settings, assets, csp, redirects);
+    if rules.global.is_empty() && rules.paths.is_empty() && rules.redirects.is_empty() {
+        return Ok(());
+    }
+    let targets = settings.map_or(&[DeployTarget::Cloudflare][..], |s| &s.targets[..]);
+    let mut written: Vec<&str> = Vec::new();
+    for target in targets {
+        match target {
+            DeployTarget::Cloudflare => {
+                if !rules.global.is_empty() || !rules.paths.is_empty() {
+                    write_text(root, site_dir, "_headers", &headers_file(&prefix, &rules))?;
+                    written.push("_headers");
+                }
+                if !rules.redirects.is_empty() {
+                    write_text(root, site_dir, "_redirects", &redirects_file(&rules))?;
+                    written.push("_redirects");
+                }
+            }
+            DeployTarget::Vercel => {
+                write_vercel(root, site_dir, &prefix, &rules)?;
+                written.push("vercel.json");
+            }
+            DeployTarget::Nginx => {
+                write_text(root, site_dir, NGINX_FILE, &nginx_file(&rules))?;
+                written.push(NGINX_FILE);
+            }
+        }
+    }
+    written.dedup();
+    println!(
+        "🚚 部署文件: {}（重定向 {} 条）",
+        written.join(", "),
+        rules.redirects.len()
+    );
+    Ok(())
+}
+
+fn collect_rules(
+    prefix: &str,
+    settings: Option<&DeploySettings>,
+    assets: &AssetManifest,
+    csp: Option<&str>,
+    redirects: &[Redirect],
+) -> Rules {
+    let mut global: Vec<(String, String)> = Vec::new();
+    let mut paths: Vec<(String, Vec<(String, String)>)> = Vec::new();
+    if let Some(s) = settings {
+        if s.security_headers.unwrap_or(true) {
+            for (k, v) in [
+                ("X-Content-Type-Options", "nosniff"),
+                ("Referrer-Policy", "strict-origin-when-cross-origin"),
+                ("X-Frame-Options", "SAMEORIGIN"),
+                (
+                    "Permissions-Policy",
+                    "camera=(), microphone=(), geolocation=()",
+                ),
+            ] {
+                global.push((k.to_string(), v.to_string()));
+            }
+        }
+        if s.hsts {
+            global.push((
+                "Strict-Transport-Security".to_string(),
+                "max-age=31536000; includeSubDomains".to_string(),
+            ));
+        }
+        for (k, v) in &s.headers {
+            global.retain(|(g, _)| !g.eq_ignore_ascii_case(k));
+            global.push((k.clone(), v.clone()));
+        }
+        if s.cache_assets.unwrap_or(true) {
+            for path in assets.fingerprinted() {
+                paths.push((
+                    format!("{}{}", prefix, path),
+                    vec![("Cache-Control".to_string(), IMMUTABLE.to_string())],
+                ));
+            }
+            // Service Worker 需及时更新
+            paths.push((
+                format!("{}sw.js", prefix),
+                vec![("Cache-Control".to_string(), "no-cache".to_string())],
+            ));
+        }
+    }
+    if let Some(policy) = csp {
+        global.push(("Content-Security-Policy".to_string(), policy.to_string()));
+    }
+    let redirects = redirects
+        .iter()
+        .map(|r| {
+            let to = if r.is_internal() {
+                encode_path(&format!("{}{}", prefix, r.to))
+            } else {
+                r.to.replace(' ', "%20")
+            };
+            // from 保持原字符：nginx 按解码后的路径匹配，其余平台写出时再编码
+            (format!("{}{}", prefix, r.from), to)
+        })
+        .collect();
+    Rules {
+        global,
+        paths,
+        redirects,
+    }
+}
+
+/// 目录路径同时匹配带与不带结尾斜杠的请求
+fn with_slash_variants(from: &str) -> Vec<String> {
+    match from.strip_suffix('/') {
+        Some(bare) if !bare.is_empty() => vec![from.to_string(), bare.to_string()],
+        _ => vec![from.to_string()],
+    }
+}
+
+fn headers_file(prefix: &str, rules: &Rules) -> String {
+    let mut out = String::new();
+    let mut block = |path: &str, headers: &[(String, String)]| {
+        out.push_str(path);
+        out.push('\n');
+        for (k, v) in headers {
+            out.push_str(&format!("  {}: {}\n", k, v));
+        }
+    };
+    if !rules.global.is_empty() {
+        block(&format!("{}*", prefix), &rules.global);
+    }
+    for (path, headers) in &rules.paths {
+        block(path, headers);
+    }
+    out
+}
+
+fn redirects_file(rules: &Rules) -> String {
+    let mut out = String::new();
+    for (from, to) in &rules.redirects {
+        for path in with_slash_variants(&encode_path(from)) {
+            out.push_str(&format!("{} {} 301\n", path, to));
+        }
+    }
+    out
+}
+
+fn nginx_file(rules: &Rules) -> String {
+    let quote = |s: &str| format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""));
+    let add_headers = |out: &mut String, indent: &str, headers: &[(String, String)]| {
+        for (k, v) in headers {
+            out.push_str(&format!(
+                "{}add_header {} {} always;\n",
+                indent,
+                k,
+                quote(v)
+            ));
+        }
+    };
+    let mut out = String::from(
+        "# 由 dove 生成：在 server { } 中 include 本文件\n\
+         # location 内的 add_header 会覆盖 server 级设置，因此每个 location 重复全站响应头\n",
+    );
+    add_headers(&mut out, "", &rules.global);
+    for (path, headers) in &rules.paths {
+        out.push_str(&format!("\nlocation = {} {{\n", path));
+        add_headers(&mut out, "    ", headers);
+        add_headers(&mut out, "    ", &rules.global);
+        out.push_str("}\n");
+    }
+    for (from, to) in &rules.redirects {
+        for path in with_slash_variants(from) {
+            // `$` 在 nginx 字符串中表示变量
+            out.push_str(&format!(
+                "\nlocation = {} {{\n    return 301 {};\n}}\n",
+                path,
+                quote(&to.replace('$', "%24"))
+            ));
+        }
+    }
+    out
+}
+
+#[derive(Serialize)]
+struct VercelHeader<'a> {
+    key: &'a str,
+    value: &'a str,
+}
+
+#[derive(Serialize)]
+struct VercelHeaderRule<'a> {
+    source: String,
+    headers: Vec<VercelHeader<'a>>,
+}
+
+#[derive(Serialize)]
+struct VercelRedirect<'a> {
+    source: String,
+    destination: &'a str,
+    permanent: bool,
+}
+
+fn vercel_headers(headers: &[(String, String)]) -> Vec<VercelHeader<'_>> {
+    headers
+        .iter()
+        .map(|(k, v)| VercelHeader { key: k, value: v })
+        .collect()
+}
+
+fn write_vercel(root: &Path, site_dir: &Path, prefix: &str, rules: &Rules) -> Result<()> {
+    let mut header_rules = Vec::new();
+    if !rules.global.is_empty() {
+        header_rules.push(VercelHeaderRule {
+            source: format!("{}(.*)", prefix),
+            headers: vercel_headers(&rules.global),
+        });
+    }
+    for (path, headers) in &rules.paths {
+        header_rules.push(VercelHeaderRule {
+            source: path.clone(),
+            headers: vercel_headers(headers),
+        });
+    }
+    let redirects: Vec<VercelRedirect> = rules
+        .redirects
+        .iter()
+        .flat_map(|(from, to)| {
+            with_slash_variants(&encode_path(from))
+                .into_iter()
+                .map(move |source| VercelRedirect {
+                    source,
+                    destination: to,
+                    permanent: true,
+                })
+        })
+        .collect();
+
+    // 合并静态目录中已有的 vercel.json：保留其它字段，headers/redirects 追加
+    let existing = site_dir.join("vercel.json");
+    let mut doc = match fs::read_to_string(&existing) {
+        Ok(text) => {
+            let parsed: serde_yaml::Value = serde_yaml::from_str(&text)
+                .with_context(|| format!("解析 vercel.json 失败: {}", existing.display()))?;
+            tera::to_value(parsed)?
+        }
+        Err(_) => tera::Value::Object(tera::Map::new()),
+    };
+    let Some(obj) = doc.as_object_mut() else {
+        bail!("vercel.json 顶层必须是对象: {}", existing.display());
+    };
+    for (key, generated) in [
+        ("headers", tera::to_value(&header_rules)?),
+        ("redirects", tera::to_value(&redirects)?),
+    ] {
+        let Some(items) = generated.as_array().filter(|a| !a.is_empty()) else {
+            continue;
+        };
+        let entry = obj
+            .entry(key)
+            .or_insert_with(|| tera::Value::Array(Vec::new()));
+        match entry.as_array_mut() {
+            Some(list) => list.extend(items.iter().cloned()),
+            None => bail!("vercel.json 中的 {} 必须是数组", key),
+        }
+    }
+    if root != site_dir && existing.exists() {
+        fs::remove_file(&existing)?;
+    }
+    fs::write(root.join("vercel.json"), format!("{}\n", doc)).context("写入 vercel.json 失败")
+}
+
+/// 写出文本部署文件；静态目录提供的同名文件内容保留在前
+fn write_text(root: &Path, site_dir: &Path, name: &str, generated: &str) -> Result<()> {
+    let existing = site_dir.join(name);
+    let mut text = fs::read_to_string(&existing).unwrap_or_default();
+    if root != site_dir && existing.exists() {
+        fs::remove_file(&existing)?;
+    }
+    if !text.is_empty() && !text.ends_with('\n') {
+        text.push('\n');
+    }
+    text.push_str(generated);
+    fs::write(root.join(name), text).with_context(|| format!("写入 {} 失败", name))
+}
+
+/// 为每条重定向写出最小跳转页 `<from>/index.html`（meta refresh），适用于不支持重定向规则的托管
+pub(crate) fn write_redirect_pages(site_dir: &Path, redirects: &[Redirect]) -> Result<()> {
+    for r in redirects {
+        let dir = site_dir.join(r.from.trim_end_matches('/'));
+        fs::create_dir_all(&dir)?;
+        // 站内路径按页面深度换算为相对地址，部署在 base_path 下同样可用
+        let target = if r.is_internal() {
+            let depth = r.from.trim_end_matches('/').split('/').count();
+            format!("{}{}", "../".repeat(depth), encode_path(&r.to))
+        } else {
+            r.to.clone()
+        };
+        let url = escape_attr(&target);
+        let canonical = if r.is_internal() {
+            String::new()
+        } else {
+            format!("<link rel=\"canonical\" href=\"{url}\">")
+        };
+        let html = format!(
+            "<!doctype html><html><head><meta charset=\"utf-8\"><meta name=\"robots\" content=\"noindex\"><meta http-equiv=\"refresh\" content=\"0; url={url}\">{canonical}<title>跳转中…</title></head><body><a href=\"{url}\">{url}</a></body></html>\n"
+        );
+        fs::write(dir.join("index.html"), html)
+            .with_context(|| format!("写入跳转页失败: {}index.html", r.from))?;
+    }
+    Ok(())
+}
+
+/// 是否以 RFC 3986 的 `scheme:` 开头（字母开头，后接字母、数字、`+`、`-`、`.`）
+fn has_scheme(url: &str) -> bool {
+    let Some((scheme, _)) = url.split_once(':') else {
+        return false;
+    };
+    scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+        && scheme
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+}
+
+fn escape_attr(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
@@ -0,0 +1,151 @@
+//! 静态资源指纹模块：
+//! - 为站点 `assets/` 下的文件按内容哈希生成带指纹的副本（如 `styles.3f2a9c1d.css`）
+//! - 提供 Tera `asset()` 函数，模板据此引用带指纹的文件名
+//! - 计算构建哈希并注入 Service Worker（缓存名与预缓存列表）
+
+use anyhow::{Context, Result};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::Path,
+};
+use tera::Tera;
+
+use crate::{precache::PrecacheEntry, utils::fnv1a64};
+
+/// 资源清单：原路径（相对站点根，如 `assets/styles.css`）-> 带指纹路径
+#[derive(Debug, Clone, Default)]
+pub(crate) struct AssetManifest {
+    entries: BTreeMap<String, String>,
+    build_hash: String,
+}
+
+impl AssetManifest {
+    /// 查找带指纹的路径；未登记的资源原样返回
+    pub(crate) fn resolve(&self, path: &str) -> String {
+        let leading_slash = path.starts_with('/');
+        let key = path.trim_start_matches('/');
+        match self.entries.get(key) {
+            Some(v) if leading_slash => format!("/{}", v),
+            Some(v) => v.clone(),
+            None => path.to_string(),
+        }
+    }
+
+    /// 该路径（相对站点根）是否已生成带指纹的副本
+    pub(crate) fn has_fingerprint(&self, path: &str) -> bool {
+        self.entries.contains_key(path)
+    }
+
+    /// 全部带指纹的路径（相对站点根）
+    pub(crate) fn fingerprinted(&self) -> impl Iterator<Item = &str> {
+        self.entries.values().map(String::as_str)
+    }
+
+    /// 构建哈希（由全部资源指纹计算）
+    pub(crate) fn build_hash(&self) -> &str {
+        &self.build_hash
+    }
+
+    /// 注册 Tera 函数 `asset(path="assets/styles.css")`
+    pub(crate) fn register(&self, tera: &mut Tera) {
+        let manifest = self.clone();
+        tera.register_function(
+            "asset",
+            move |args: &HashMap<String, tera::Value>| -> tera::Result<tera::Value> {
+                let path = args
+                    .get("path")
+                    .and_then(|v| v.as_str())
+                    .ok_or_else(|| tera::Error::msg("asset() 需要字符串参数 path"))?;
+                Ok(tera::Value::String(manifest.resolve(path)))
+            },
+        );
+    }
+
+    /// 在 Service Worker 顶部注入 `self.DOVE_BUILD = { hash, assets, precache }`
+    ///
+    /// 注入的 `hash` 同时覆盖资源指纹与预缓存清单的内容哈希，任一页面变化都会更换缓存名。
+    pub(crate) fn inject_service_worker(
+        &self,
+        sw_path: &Path,
+        precache: &[PrecacheEntry],
+    ) -> Result<()> {
+        let script = fs::read_to_string(sw_path)
+            .with_context(|| format!("读取 Service Worker 失败: {}", sw_path.display()))?;
+        let mut acc = self.build_hash.clone();
+        for e in precache {
+            acc.push('\n');
+            acc.push_str(&e.url);
+            acc.push('=');
+            acc.push_str(&e.revision);
+        }
+        let header = format!(
+            "self.DOVE_BUILD = {{ hash: {}, assets: {}, precache: {} }};\n",
+            tera::Value::String(short_hash(acc.as_bytes())),
+            tera::to_value(&self.entries)?,
+            tera::to_value(precache)?
+        );
+        fs::write(sw_path, format!("{}{}", header, script))
+            .with_context(|| format!("写入 Service Worker 失败: {}", sw_path.display()))?;
+        Ok(())
+    }
+}
+
+/// 为 `site_dir/assets` 下的资源生成带指纹副本（保留原文件以兼容硬编码引用）
+///
+/// 跳过 `sw.js`（需固定 URL）与 HTML 文件（如离线页，由 Service Worker 按固定名引用）。
+pub(crate) fn fingerprint_assets(site_dir: &Path) -> Result<AssetManifest> {
+    let mut manifest = AssetManifest::default();
+    let assets_dir = site_dir.join("assets");
+    if assets_dir.is_dir() {
+        collect(&assets_dir, "assets", &mut manifest.entries)?;
+    }
+    let mut acc = String::new();
+    for (k, v) in &manifest.entries {
+        acc.push_str(k);
+        acc.push('=');
+        acc.push_str(v);
+        acc.push('\n');
+    }
+    manifest.build_hash = short_hash(acc.as_bytes());
+    Ok(manifest)
+}
+
+fn collect(dir: &Path, rel: &str, out: &mut BTreeMap<String, String>) -> Result<()> {
+    let mut entries: Vec<_> = fs::read_dir(dir)?.collect::<std::io::Result<_>>()?;
+    entries.sort_by_key(|e| e.file_name());
+    for entry in entries {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.starts_with('.') {
+            continue;
+        }
+        let path = entry.path();
+        let rel_path = format!("{}/{}", rel, name);
+        if entry.file_type()?.is_dir() {
+            collect(&path, &rel_path, out)?;
+            continue;
+        }
+        let lower = name.to_ascii_lowercase();
+        if lower == "sw.js" || lower.ends_with(".html") {
+            continue;
+        }
+        let bytes = fs::read(&path).with_context(|| format!("读取资源失败: {}", path.display()))?;
+        let hashed_name = fingerprinted_name(&name, &short_hash(&bytes));
+        fs::write(dir.join(&hashed_name), &bytes)
+            .with_context(|| format!("写入指纹资源失败: {}/{}", rel, hashed_name))?;
+        out.insert(rel_path, format!("{}/{}", rel, hashed_name));
+    }
+    Ok(())
+}
+
+/// `styles.css` + `3f2a9c1d` -> `styles.3f2a9c1d.css`
+fn fingerprinted_name(name: &str, hash: &str) -> String {
+    match name.rfind('.') {
+        Some(idx) if idx > 0 => format!("{}.{}{}", &name[..idx], hash, &name[idx..]),
+        _ => format!("{}.{}", name, hash),
+    }
+}
+
+pub(crate) fn short_hash(data: &[u8]) -> String {
+    format!("{:016x}", fnv1a64(data))[..8].to_string()
+}
@@ -6,10 +6,7 @@
 use std::{collections::HashMap, path::Path};
 
 #[cfg(feature = "remote")]
-use std::{fs, io::Read};
-
-#[cfg(feature = "remote")]
-use crate::config::load_config; // not used directly; keep feature parity
+use std::io::Read;
 
 #[cfg(feature = "remote")]
 use anyhow::Result;
@@ -49,7 +46,7 @@ pub(crate) fn download_icons_concurrent(
     let (txr, rxr) = mpsc::channel::<(String, Option<String>)>();
     let total = targets.len();
     let workers = threads.min(total.max(1));
-    let chunk_size = (total + workers - 1) / workers; // 向上取整
+    let chunk_size = total.div_ceil(workers); // 向上取整
     for chunk_idx in 0..workers {
         let start = chunk_idx * chunk_size;
         let end = (start + chunk_size).min(total);
@@ -124,7 +121,7 @@ fn download_one_icon(url: &str, dest_dir: &Path) -> Option<String> {
         return None;
     }
     // 文件名：对 URL 做 FNV-1a 64 哈希
-    let hash = fnv1a64(url.as_bytes());
+    let hash = crate::utils::fnv1a64(url.as_bytes());
     let fname = format!("i_{:016x}.{}", hash, ext);
     let fpath = dest_dir.join(&fname);
     if !fpath.exists() {
@@ -166,7 +163,7 @@ fn ext_from_headers_or_url(content_type: &str, url: &str) -> &'static str {
         _ => {
             // 尝试从 URL path 提取
             if let Ok(u) = url::Url::parse(url) {
-                if let Some(seg) = u.path_segments().and_then(|it| it.last()) {
+                if let Some(seg) = u.path_segments().and_then(|mut it| it.next_back()) {
                     if let Some(idx) = seg.rfind('.') {
                         return match &seg[idx + 1..].to_ascii_lowercase()[..] {
                             "svg" => "svg",
@@ -185,15 +182,3 @@ fn ext_from_headers_or_url(content_type: &str, url: &str) -> &'static str {
         }
     }
 }
-
-#[cfg(feature = "remote")]
-fn fnv1a64(data: &[u8]) -> u64 {
-    const FNV_OFFSET: u64 = 0xcbf29ce484222325;
-    const FNV_PRIME: u64 = 0x00000100000001b3;
-    let mut hash = FNV_OFFSET;
-    for b in data {
-        hash ^= *b as u64;
-        hash = hash.wrapping_mul(FNV_PRIME);
-    }
-    hash
-}
@@ -2,7 +2,7 @@
 //! - `dove init` 写出示例配置与内置默认主题
 
 use anyhow::{Context, Result};
-use include_dir::{include_dir, Dir};
+use include_dir::{include_dir, Dir, File};
 use std::{fs, path::Path};
 
 // 内置示例（用于 init）
@@ -66,9 +66,27 @@ pub(crate) fn copy_dir_all(from: &Path, to: &Path) -> Result<()> {
     Ok(())
 }
 
+/// 内置默认主题的全部文件（递归；路径相对主题根，如 `templates/index.html.tera`）
+pub(crate) fn default_theme_files() -> Vec<&'static File<'static>> {
+    fn walk(dir: &'static Dir<'static>, out: &mut Vec<&'static File<'static>>) {
+        out.extend(dir.files());
+        for sub in dir.dirs() {
+            walk(sub, out);
+        }
+    }
+    let mut out = Vec::new();
+    walk(&DEFAULT_THEME_DIR, &mut out);
+    out
+}
+
+/// 内置默认主题中的单个文件（路径相对主题根）
+pub(crate) fn default_theme_file(rel: &str) -> Option<&'static File<'static>> {
+    DEFAULT_THEME_DIR.get_file(rel)
+}
+
 /// 将内置默认主题写出到指定目录
 pub(crate) fn write_default_theme(target_dir: &Path) -> Result<()> {
-    for f in DEFAULT_THEME_DIR.files() {
+    for f in default_theme_files() {
         let rel = f.path();
         let out_path = target_dir.join(rel);
         if let Some(parent) = out_path.parent() {
@@ -0,0 +1,57 @@
+//! 局域网预览模块：
+//! - 探测本机局域网 IP，生成手机可访问的地址
+//! - 在终端以字符二维码打印地址，便于扫码
+//! - 生成自签名证书，供 HTTPS 预览（`--features https`）
+
+use std::net::{IpAddr, UdpSocket};
+
+use qrcode::{render::unicode::Dense1x2, QrCode};
+
+/// 本机用于对外通信的局域网 IP（仅查询路由，不会真正发送数据）
+pub(crate) fn lan_ip() -> Option<IpAddr> {
+    let socket = UdpSocket::bind("0.0.0.0:0").ok()?;
+    socket.connect("8.8.8.8:80").ok()?;
+    let ip = socket.local_addr().ok()?.ip();
+    (!ip.is_loopback() && !ip.is_unspecified()).then_some(ip)
+}
+
+/// 将监听地址改为所有网卡（保留端口）：`127.0.0.1:8787` -> `0.0.0.0:8787`
+pub(crate) fn bind_all_interfaces(addr: &str) -> String {
+    let port = addr.rsplit_once(':').map(|(_, p)| p).unwrap_or("8787");
+    format!("0.0.0.0:{}", port)
+}
+
+/// 在终端打印二维码（深色背景终端下亮暗反转，保证可扫）
+pub(crate) fn print_qr(text: &str) {
+    match QrCode::new(text.as_bytes()) {
+        Ok(code) => {
+            let art = code
+                .render::<Dense1x2>()
+                .dark_color(Dense1x2::Light)
+                .light_color(Dense1x2::Dark)
+                .quiet_zone(true)
+                .build();
+            println!("{}", art);
+        }
+        Err(e) => eprintln!("警告: 生成二维码失败: {}", e),
+    }
+}
+
+/// 生成自签名证书（PEM 证书, PEM 私钥），SAN 包含 localhost、回环地址与给定主机
+#[cfg(feature = "https")]
+pub(crate) fn self_signed_cert(hosts: &[String]) -> anyhow::Result<(Vec<u8>, Vec<u8>)> {
+    use anyhow::Context;
+
+    let mut names = vec!["localhost".to_string(), "127.0.0.1".to_string()];
+    for h in hosts {
+        if !names.contains(h) {
+            names.push(h.clone());
+        }
+    }
+    let cert = rcgen::generate_simple_self_signed(names).context("生成自签名证书失败")?;
+    let pem = cert.serialize_pem().context("序列化自签名证书失败")?;
+    Ok((
+        pem.into_bytes(),
+        cert.serialize_private_key_pem().into_bytes(),
+    ))
+}
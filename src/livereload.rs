@@ -0,0 +1,184 @@
+//! 热刷新推送模块（供 preview 使用）：
+//! - `/__dove__/events` 服务器推送事件（SSE）长连接，重建后通知所有页面
+//! - 仅样式表变化时推送 `css` 事件，页面原地替换样式而不整页刷新
+//! - 构建失败时推送 `build-error` 事件，页面显示错误浮层，修复后自动刷新
+//! - 记录重建状态，供 `/__dove__/status` 以 JSON 返回
+
+use std::{
+    io::Write,
+    sync::{
+        mpsc::{self, RecvTimeoutError, Sender},
+        Arc, Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use serde::Serialize;
+use tiny_http::Request;
+
+/// 注入到 HTML 页面的客户端脚本（插入在 `</body>` 之前）
+pub(crate) const CLIENT_SCRIPT: &str = r#"<script>(function(){
+if(!window.EventSource)return;
+var es=new EventSource('/__dove__/events');
+function overlay(msg){var o=document.getElementById('__dove_overlay');if(msg===null){if(o)o.remove();return;}
+if(!o){o=document.createElement('div');o.id='__dove_overlay';o.setAttribute('style','position:fixed;inset:0;z-index:2147483647;background:rgba(20,20,20,.94);color:#ffb4b4;font:13px/1.6 ui-monospace,Menlo,Consolas,monospace;padding:24px;overflow:auto;white-space:pre-wrap');document.body.appendChild(o);}
+o.textContent='⚠ 构建失败（修复后自动刷新）\n\n'+msg;}
+function swapCss(){fetch(location.href,{cache:'no-store'}).then(function(r){return r.text();}).then(function(t){
+var doc=new DOMParser().parseFromString(t,'text/html');var nl=doc.querySelectorAll('link[rel="stylesheet"]');var ol=document.querySelectorAll('link[rel="stylesheet"]');
+if(nl.length!==ol.length){location.reload();return;}
+ol.forEach(function(l,i){var h=nl[i].getAttribute('href')||'';var n=document.createElement('link');n.rel='stylesheet';n.href=h+(h.indexOf('?')<0?'?':'&')+'_dove='+Date.now();n.onload=function(){l.remove();};l.after(n);});
+}).catch(function(){location.reload();});}
+es.addEventListener('reload',function(){location.reload();});
+es.addEventListener('css',function(){overlay(null);swapCss();});
+es.addEventListener('build-error',function(e){overlay(e.data);});
+})();</script>
+"#;
+
+/// 推送给页面的事件
+#[derive(Debug, Clone)]
+enum Event {
+    Reload,
+    Css,
+    BuildError(String),
+}
+
+impl Event {
+    /// 编码为 SSE 帧；多行数据逐行加 `data:` 前缀
+    fn frame(&self) -> String {
+        let (name, data) = match self {
+            Event::Reload => ("reload", ""),
+            Event::Css => ("css", ""),
+            Event::BuildError(msg) => ("build-error", msg.as_str()),
+        };
+        let mut out = format!("event: {}\n", name);
+        for line in data.split('\n') {
+            out.push_str("data: ");
+            out.push_str(line.trim_end_matches('\r'));
+            out.push('\n');
+        }
+        out.push('\n');
+        out
+    }
+}
+
+/// 重建状态（`/__dove__/status` 的响应体）
+#[derive(Debug, Clone, Default, Serialize)]
+pub(crate) struct BuildStatus {
+    /// 成功重建的次数
+    version: u64,
+    /// 是否正在重建
+    building: bool,
+    /// 最近一次重建的错误（含完整上下文）；成功后清空
+    error: Option<String>,
+    /// 最近一次重建的配置来源
+    source: Option<String>,
+    /// 最近一次重建结束时间（RFC 3339）
+    finished_at: Option<String>,
+    /// 最近一次重建耗时（毫秒）
+    duration_ms: Option<u64>,
+}
+
+/// 热刷新中心：记录已连接的页面与重建状态
+#[derive(Default)]
+pub(crate) struct LiveReload {
+    clients: Mutex<Vec<Sender<Event>>>,
+    status: Mutex<BuildStatus>,
+}
+
+impl LiveReload {
+    /// 当前版本号（每次成功重建加一）
+    pub(crate) fn version(&self) -> u64 {
+        self.status.lock().unwrap().version
+    }
+
+    /// 当前状态的 JSON 表示
+    pub(crate) fn status_json(&self) -> String {
+        let status = self.status.lock().unwrap().clone();
+        tera::to_value(status)
+            .map(|v| v.to_string())
+            .unwrap_or_else(|_| "{}".to_string())
+    }
+
+    /// 开始重建
+    pub(crate) fn build_started(&self) {
+        self.status.lock().unwrap().building = true;
+    }
+
+    /// 重建成功：此前失败或非纯样式变更时整页刷新，否则热替换样式；返回新版本号
+    pub(crate) fn build_succeeded(&self, css_only: bool, source: String, elapsed: Duration) -> u64 {
+        let (had_error, version) = {
+            let mut status = self.status.lock().unwrap();
+            let had_error = status.error.take().is_some();
+            status.version += 1;
+            finish(&mut status, source, elapsed);
+            (had_error, status.version)
+        };
+        if css_only && !had_error {
+            self.broadcast(Event::Css);
+        } else {
+            self.broadcast(Event::Reload);
+        }
+        version
+    }
+
+    /// 重建失败：记录错误并推送到页面浮层（新连接的页面也会立即收到）
+    pub(crate) fn build_failed(&self, message: String, elapsed: Duration) {
+        {
+            let mut status = self.status.lock().unwrap();
+            status.error = Some(message.clone());
+            // 配置可能未能加载，沿用上一次的来源
+            let source = status.source.take().unwrap_or_default();
+            finish(&mut status, source, elapsed);
+        }
+        self.broadcast(Event::BuildError(message));
+    }
+
+    fn broadcast(&self, event: Event) {
+        self.clients
+            .lock()
+            .unwrap()
+            .retain(|tx| tx.send(event.clone()).is_ok());
+    }
+
+    /// 接管 `/__dove__/events` 请求：在独立线程中保持连接并逐条写出事件
+    pub(crate) fn serve_events(self: &Arc<Self>, rq: Request) {
+        let (tx, rx) = mpsc::channel();
+        if let Some(msg) = self.status.lock().unwrap().error.clone() {
+            let _ = tx.send(Event::BuildError(msg));
+        }
+        self.clients.lock().unwrap().push(tx);
+        thread::spawn(move || {
+            // tiny_http 的分块编码会缓冲输出，这里直接写原始响应并逐条 flush
+            let mut w = rq.into_writer();
+            let head = "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream; charset=utf-8\r\nCache-Control: no-store\r\nConnection: close\r\n\r\nretry: 1000\n\n";
+            if w.write_all(head.as_bytes())
+                .and_then(|_| w.flush())
+                .is_err()
+            {
+                return;
+            }
+            loop {
+                let frame = match rx.recv_timeout(Duration::from_secs(15)) {
+                    Ok(event) => event.frame(),
+                    // 定期发送注释行保活，同时发现已断开的连接
+                    Err(RecvTimeoutError::Timeout) => ": ping\n\n".to_string(),
+                    Err(RecvTimeoutError::Disconnected) => return,
+                };
+                if w.write_all(frame.as_bytes())
+                    .and_then(|_| w.flush())
+                    .is_err()
+                {
+                    return;
+                }
+            }
+        });
+    }
+}
+
+fn finish(status: &mut BuildStatus, source: String, elapsed: Duration) {
+    status.building = false;
+    status.source = Some(source).filter(|s| !s.is_empty());
+    status.finished_at = Some(chrono::Local::now().to_rfc3339());
+    status.duration_ms = Some(elapsed.as_millis() as u64);
+}
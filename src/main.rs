@@ -5,9 +5,27 @@ mod build;
 mod cli;
 mod commands;
 mod config;
+mod csp;
+mod deploy;
+mod fingerprint;
 mod icons;
 mod init;
+mod lan;
+mod livereload;
+mod markdown;
+mod minify;
+mod pages;
+mod precache;
+mod precompress;
 mod preview;
+mod sanitize;
+mod serve;
+mod shortlinks;
+mod simulate;
+mod slugs;
+mod staging;
+mod theme;
+mod theme_pkg;
 mod utils;
 
 use anyhow::Result;
@@ -0,0 +1,157 @@
+//! Markdown 渲染模块（构建期）：
+//! - 统一的 Markdown 扩展选项（表格、脚注、删除线、任务列表、标题属性）
+//! - 代码块按语言做语法高亮（syntect，输出 `hl-` 前缀的 CSS 类，由主题样式着色）
+//! - 链接简介的行内渲染与纯文本提取；纯文本详情转为段落
+
+use anyhow::{Context, Result};
+use pulldown_cmark::{html, CodeBlockKind, CowStr, Event, Options, Parser, Tag, TagEnd};
+use std::sync::OnceLock;
+use syntect::{
+    html::{ClassStyle, ClassedHTMLGenerator},
+    parsing::{SyntaxReference, SyntaxSet},
+    util::LinesWithEndings,
+};
+
+/// 高亮输出的 CSS 类前缀（如 `hl-keyword`）
+const CLASS_STYLE: ClassStyle = ClassStyle::SpacedPrefixed { prefix: "hl-" };
+
+fn syntax_set() -> &'static SyntaxSet {
+    static SET: OnceLock<SyntaxSet> = OnceLock::new();
+    SET.get_or_init(SyntaxSet::load_defaults_newlines)
+}
+
+/// 按统一选项解析 Markdown
+pub(crate) fn parse(text: &str) -> Parser<'_> {
+    let mut options = Options::empty();
+    options.insert(Options::ENABLE_TABLES);
+    options.insert(Options::ENABLE_FOOTNOTES);
+    options.insert(Options::ENABLE_STRIKETHROUGH);
+    options.insert(Options::ENABLE_TASKLISTS);
+    options.insert(Options::ENABLE_HEADING_ATTRIBUTES);
+    Parser::new_ext(text, options)
+}
+
+/// 将事件流输出为 HTML，已知语言的围栏代码块替换为高亮结果
+pub(crate) fn to_html<'a>(events: impl IntoIterator<Item = Event<'a>>) -> Result<String> {
+    let mut out: Vec<Event<'a>> = Vec::new();
+    // 正在收集的代码块：(语言, 代码)
+    let mut block: Option<(String, String)> = None;
+    for ev in events {
+        match ev {
+            Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(ref lang)))
+                if find_syntax(lang).is_some() =>
+            {
+                block = Some((lang.to_string(), String::new()));
+            }
+            Event::Text(ref t) if block.is_some() => {
+                if let Some((_, code)) = block.as_mut() {
+                    code.push_str(t);
+                }
+            }
+            Event::End(TagEnd::CodeBlock) if block.is_some() => {
+                if let Some((lang, code)) = block.take() {
+                    out.push(Event::Html(highlight(&lang, &code)?.into()));
+                }
+            }
+            other => out.push(other),
+        }
+    }
+    let mut html_out = String::new();
+    html::push_html(&mut html_out, out.into_iter());
+    Ok(html_out)
+}
+
+/// 渲染完整 Markdown 文档
+pub(crate) fn render(text: &str) -> Result<String> {
+    to_html(parse(text))
+}
+
+/// 行内渲染（用于简介）：去掉段落包裹；链接仅保留文字，避免嵌套在卡片链接中。
+/// 返回 (HTML, 纯文本)，纯文本用于 meta 描述与搜索数据
+pub(crate) fn render_inline(text: &str) -> (String, String) {
+    let mut plain = String::new();
+    let events: Vec<Event> = parse(text)
+        .filter_map(|ev| match ev {
+            Event::Start(Tag::Paragraph) | Event::Start(Tag::Link { .. }) => None,
+            Event::End(TagEnd::Link) => None,
+            Event::End(TagEnd::Paragraph) => {
+                plain.push(' ');
+                Some(Event::Text(CowStr::Borrowed(" ")))
+            }
+            Event::Text(t) => {
+                plain.push_str(&t);
+                Some(Event::Text(t))
+            }
+            Event::Code(t) => {
+                plain.push_str(&t);
+                Some(Event::Code(t))
+            }
+            Event::SoftBreak | Event::HardBreak => {
+                plain.push(' ');
+                Some(Event::Text(CowStr::Borrowed(" ")))
+            }
+            other => Some(other),
+        })
+        .collect();
+    let mut html_out = String::new();
+    html::push_html(&mut html_out, events.into_iter());
+    (
+        html_out.trim().to_string(),
+        plain.split_whitespace().collect::<Vec<_>>().join(" "),
+    )
+}
+
+/// 纯文本转 HTML：转义后按空行分段，段内换行保留为 `<br>`
+pub(crate) fn text_to_html(text: &str) -> String {
+    let mut out = String::new();
+    for para in text.split("\n\n").map(str::trim).filter(|p| !p.is_empty()) {
+        let lines: Vec<String> = para.lines().map(escape_html).collect();
+        out.push_str("<p>");
+        out.push_str(&lines.join("<br>\n"));
+        out.push_str("</p>\n");
+    }
+    out
+}
+
+/// 围栏代码块信息串的语言部分（```rust,ignore -> rust）
+fn lang_token(info: &str) -> &str {
+    info.split([',', ' ']).next().unwrap_or("").trim()
+}
+
+fn find_syntax(info: &str) -> Option<&'static SyntaxReference> {
+    let token = lang_token(info);
+    if token.is_empty() {
+        return None;
+    }
+    syntax_set().find_syntax_by_token(token)
+}
+
+fn highlight(lang: &str, code: &str) -> Result<String> {
+    let ss = syntax_set();
+    let syntax = find_syntax(lang).unwrap_or_else(|| ss.find_syntax_plain_text());
+    let mut generator = ClassedHTMLGenerator::new_with_class_style(syntax, ss, CLASS_STYLE);
+    for line in LinesWithEndings::from(code) {
+        generator
+            .parse_html_for_line_which_includes_newline(line)
+            .with_context(|| format!("代码高亮失败（语言 {}）", lang))?;
+    }
+    Ok(format!(
+        "<pre class=\"highlight\"><code class=\"language-{}\">{}</code></pre>\n",
+        escape_html(lang_token(lang)),
+        generator.finalize()
+    ))
+}
+
+fn escape_html(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
@@ -0,0 +1,153 @@
+//! 输出压缩模块：
+//! - 压缩渲染出的 HTML（保留 `<pre>` 内容与 JSON-LD 等非 JS 脚本）
+//! - 压缩复制到站点的 `.css`/`.js` 资源（跳过已压缩的 `*.min.*`）
+//! - 统计并打印压缩前后体积
+
+use anyhow::{Context, Result};
+use lightningcss::stylesheet::{MinifyOptions, ParserOptions, PrinterOptions, StyleSheet};
+use std::{cell::Cell, fs, path::Path};
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Saving {
+    files: usize,
+    before: u64,
+    after: u64,
+}
+
+/// 压缩器：未启用时原样返回，启用时累计各类文件的体积变化
+pub(crate) struct Minifier {
+    enabled: bool,
+    html: Cell<Saving>,
+    css: Cell<Saving>,
+    js: Cell<Saving>,
+}
+
+impl Minifier {
+    pub(crate) fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            html: Cell::new(Saving::default()),
+            css: Cell::new(Saving::default()),
+            js: Cell::new(Saving::default()),
+        }
+    }
+
+    /// 压缩一页 HTML（内联 `<style>`/`<script>` 一并压缩）
+    pub(crate) fn html(&self, html: String) -> String {
+        if !self.enabled {
+            return html;
+        }
+        let mut cfg = minify_html::Cfg::spec_compliant();
+        cfg.keep_closing_tags = true;
+        cfg.keep_html_and_head_opening_tags = true;
+        cfg.minify_css = true;
+        cfg.minify_js = true;
+        let out = minify_html::minify(html.as_bytes(), &cfg);
+        match String::from_utf8(out) {
+            Ok(min) if min.len() < html.len() => {
+                record(&self.html, html.len(), min.len());
+                min
+            }
+            _ => {
+                record(&self.html, html.len(), html.len());
+                html
+            }
+        }
+    }
+
+    /// 递归压缩目录下的 `.css`/`.js` 文件（原地写回）
+    pub(crate) fn minify_assets(&self, dir: &Path) -> Result<()> {
+        if !self.enabled || !dir.is_dir() {
+            return Ok(());
+        }
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            let path = entry.path();
+            if entry.file_type()?.is_dir() {
+                self.minify_assets(&path)?;
+                continue;
+            }
+            let name = entry.file_name().to_string_lossy().to_ascii_lowercase();
+            if name.contains(".min.") {
+                continue;
+            }
+            let (slot, minified) = if name.ends_with(".css") {
+                let code = fs::read_to_string(&path)
+                    .with_context(|| format!("读取 CSS 失败: {}", path.display()))?;
+                (&self.css, minify_css(&code).map(String::into_bytes))
+            } else if name.ends_with(".js") {
+                let code =
+                    fs::read(&path).with_context(|| format!("读取 JS 失败: {}", path.display()))?;
+                (&self.js, minify_js(&code))
+            } else {
+                continue;
+            };
+            let before = entry.metadata()?.len() as usize;
+            match minified {
+                Some(min) if min.len() < before => {
+                    fs::write(&path, &min)
+                        .with_context(|| format!("写入压缩结果失败: {}", path.display()))?;
+                    record(slot, before, min.len());
+                }
+                Some(_) => record(slot, before, before),
+                None => {
+                    eprintln!("警告: 压缩失败，保留原文件: {}", path.display());
+                    record(slot, before, before);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 打印压缩统计
+    pub(crate) fn report(&self) {
+        if !self.enabled {
+            return;
+        }
+        for (label, slot) in [("HTML", &self.html), ("CSS", &self.css), ("JS", &self.js)] {
+            let s = slot.get();
+            if s.files == 0 {
+                continue;
+            }
+            let saved = s.before.saturating_sub(s.after);
+            let pct = if s.before == 0 {
+                0.0
+            } else {
+                saved as f64 * 100.0 / s.before as f64
+            };
+            println!(
+                "🗜️ 压缩 {}: {} 个文件 {} KB -> {} KB（节省 {:.1}%）",
+                label,
+                s.files,
+                s.before.div_ceil(1024),
+                s.after.div_ceil(1024),
+                pct
+            );
+        }
+    }
+}
+
+fn record(slot: &Cell<Saving>, before: usize, after: usize) {
+    let mut s = slot.get();
+    s.files += 1;
+    s.before += before as u64;
+    s.after += after as u64;
+    slot.set(s);
+}
+
+fn minify_css(code: &str) -> Option<String> {
+    let mut sheet = StyleSheet::parse(code, ParserOptions::default()).ok()?;
+    sheet.minify(MinifyOptions::default()).ok()?;
+    let printer = PrinterOptions {
+        minify: true,
+        ..PrinterOptions::default()
+    };
+    sheet.to_css(printer).ok().map(|r| r.code)
+}
+
+fn minify_js(code: &[u8]) -> Option<Vec<u8>> {
+    let session = minify_js::Session::new();
+    let mut out = Vec::new();
+    minify_js::minify(&session, minify_js::TopLevelMode::Global, code, &mut out).ok()?;
+    Some(out)
+}
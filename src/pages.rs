@@ -0,0 +1,346 @@
+//! 自定义页面模块：
+//! - 读取页面目录（默认 `pages/`）下带 front matter 的 Markdown 文件
+//! - 渲染 Markdown，解析 `link:<slug>`（链接）与 `page:<slug>`（页面）引用
+//! - 按 `site.pages.nav` 或页面 `order` 生成导航，供模板以 `nav_pages` 使用
+
+use anyhow::{bail, Context, Result};
+use pulldown_cmark::{CowStr, Event, Tag};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    config::{ChangeFreq, PagesSettings},
+    markdown,
+};
+
+/// 页面模板缺省值
+const DEFAULT_TEMPLATE: &str = "page.html.tera";
+/// 与站点内置目录重名的 slug 不可用于页面
+const RESERVED_SLUGS: &[&str] = &["go", "intranet", "assets", "s"];
+
+/// front matter（`---` 包围的 YAML），均为可选
+#[derive(Debug, Default, Deserialize)]
+struct FrontMatter {
+    /// 标题；缺省取正文第一个一级标题，再缺省取文件名
+    #[serde(default)]
+    title: Option<String>,
+    #[serde(default)]
+    description: Option<String>,
+    /// 输出路径 `<slug>/index.html`；缺省取文件名
+    #[serde(default)]
+    slug: Option<String>,
+    /// 导航排序（升序），缺省 0
+    #[serde(default)]
+    order: i64,
+    /// 是否出现在导航中，缺省是
+    #[serde(default)]
+    nav: Option<bool>,
+    /// 导航中显示的文字，缺省同标题
+    #[serde(default)]
+    nav_title: Option<String>,
+    /// 渲染模板，缺省 `page.html.tera`
+    #[serde(default)]
+    template: Option<String>,
+    /// 草稿不输出
+    #[serde(default)]
+    draft: bool,
+    /// 是否写入 sitemap.xml，缺省是
+    #[serde(default)]
+    sitemap: Option<bool>,
+    #[serde(default)]
+    lastmod: Option<String>,
+    #[serde(default)]
+    changefreq: Option<ChangeFreq>,
+    #[serde(default)]
+    priority: Option<f32>,
+}
+
+/// 单个页面
+#[derive(Debug, Clone)]
+pub(crate) struct Page {
+    pub(crate) slug: String,
+    pub(crate) title: String,
+    pub(crate) description: String,
+    pub(crate) template: String,
+    pub(crate) sitemap: bool,
+    pub(crate) lastmod: Option<String>,
+    pub(crate) changefreq: Option<ChangeFreq>,
+    pub(crate) priority: Option<f32>,
+    nav: bool,
+    nav_title: String,
+    order: i64,
+    source: PathBuf,
+    body: String,
+}
+
+/// 导航项（`href` 已按当前页面位置换算为相对路径）
+#[derive(Debug, Serialize)]
+pub(crate) struct NavPage {
+    title: String,
+    slug: String,
+    href: String,
+    current: bool,
+}
+
+/// `link:<slug>` 引用的目标
+pub(crate) enum LinkTarget {
+    /// 中间页 `go/<slug>/`（相对站点根）
+    Detail(String),
+    /// 未生成中间页的链接，直接指向目标地址
+    Url(String),
+}
+
+/// 站点的全部页面及导航顺序
+#[derive(Debug, Default)]
+pub(crate) struct SitePages {
+    pages: Vec<Page>,
+    /// 导航中的页面（`pages` 下标）
+    nav: Vec<usize>,
+}
+
+impl SitePages {
+    /// 读取页面目录；目录不存在时返回空集合（显式配置的目录不存在时给出警告）
+    pub(crate) fn load(settings: Option<&PagesSettings>) -> Result<Self> {
+        let explicit = settings.and_then(|s| s.dir.as_deref());
+        let dir = Path::new(explicit.unwrap_or("pages"));
+        if !dir.is_dir() {
+            if explicit.is_some() {
+                eprintln!("警告: 页面目录不存在: {}", dir.display());
+            }
+            return Ok(Self::default());
+        }
+        let mut files: Vec<PathBuf> = fs::read_dir(dir)
+            .with_context(|| format!("读取页面目录失败: {}", dir.display()))?
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .filter(|p| {
+                p.is_file()
+                    && p.extension()
+                        .and_then(|e| e.to_str())
+                        .is_some_and(|e| matches!(e, "md" | "markdown"))
+            })
+            .collect();
+        files.sort();
+
+        let mut pages: Vec<Page> = Vec::new();
+        for path in files {
+            let Some(page) = Page::load(&path)? else {
+                println!("📝 跳过草稿页面: {}", path.display());
+                continue;
+            };
+            if let Some(other) = pages.iter().find(|p| p.slug == page.slug) {
+                bail!(
+                    "页面 slug 重复: {}（{} 与 {}）",
+                    page.slug,
+                    other.source.display(),
+                    path.display()
+                );
+            }
+            pages.push(page);
+        }
+        pages.sort_by(|a, b| a.order.cmp(&b.order).then_with(|| a.slug.cmp(&b.slug)));
+
+        // 导航：显式列出的 slug 按列出顺序；否则取 `nav: true` 的页面
+        let nav = match settings.and_then(|s| s.nav.as_ref()) {
+            Some(list) => list
+                .iter()
+                .filter_map(|slug| {
+                    let idx = pages.iter().position(|p| p.slug == *slug);
+                    if idx.is_none() {
+                        eprintln!("警告: site.pages.nav 中的页面不存在: {}", slug);
+                    }
+                    idx
+                })
+                .collect(),
+            None => (0..pages.len()).filter(|&i| pages[i].nav).collect(),
+        };
+        Ok(Self { pages, nav })
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.pages.is_empty()
+    }
+
+    pub(crate) fn pages(&self) -> &[Page] {
+        &self.pages
+    }
+
+    /// 导航项；`prefix` 为当前页面到站点根的相对前缀（如 `""`、`"../"`）
+    pub(crate) fn nav(&self, prefix: &str, current: Option<&str>) -> Vec<NavPage> {
+        self.nav
+            .iter()
+            .map(|&i| {
+                let p = &self.pages[i];
+                NavPage {
+                    title: p.nav_title.clone(),
+                    slug: p.slug.clone(),
+                    href: format!("{}{}/", prefix, p.slug),
+                    current: current == Some(p.slug.as_str()),
+                }
+            })
+            .collect()
+    }
+
+    /// 将页面正文渲染为 HTML（页面位于 `<slug>/`，站点内路径均以 `../` 起算）
+    pub(crate) fn render_content(
+        &self,
+        page: &Page,
+        links: &HashMap<String, LinkTarget>,
+    ) -> Result<String> {
+        let mut events = Vec::new();
+        for ev in markdown::parse(&page.body) {
+            let ev = match ev {
+                Event::Start(Tag::Link {
+                    link_type,
+                    dest_url,
+                    title,
+                    id,
+                }) => Event::Start(Tag::Link {
+                    link_type,
+                    dest_url: self.resolve_ref(page, dest_url, links)?,
+                    title,
+                    id,
+                }),
+                Event::Start(Tag::Image {
+                    link_type,
+                    dest_url,
+                    title,
+                    id,
+                }) => Event::Start(Tag::Image {
+                    link_type,
+                    dest_url: root_relative(dest_url),
+                    title,
+                    id,
+                }),
+                other => other,
+            };
+            events.push(ev);
+        }
+        markdown::to_html(events)
+    }
+
+    /// 解析 `link:<slug>` / `page:<slug>`，未知 slug 视为错误以免发布死链
+    fn resolve_ref<'a>(
+        &self,
+        page: &Page,
+        dest: CowStr<'a>,
+        links: &HashMap<String, LinkTarget>,
+    ) -> Result<CowStr<'a>> {
+        if let Some(slug) = dest.strip_prefix("link:") {
+            return match links.get(slug) {
+                Some(LinkTarget::Detail(s)) => {
+                    Ok(format!("../go/{}/", crate::slugs::encode_path(s)).into())
+                }
+                Some(LinkTarget::Url(u)) => Ok(u.clone().into()),
+                None => bail!(
+                    "页面 {} 引用了不存在的链接: {}",
+                    page.source.display(),
+                    slug
+                ),
+            };
+        }
+        if let Some(slug) = dest.strip_prefix("page:") {
+            if !self.pages.iter().any(|p| p.slug == slug) {
+                bail!(
+                    "页面 {} 引用了不存在的页面: {}",
+                    page.source.display(),
+                    slug
+                );
+            }
+            return Ok(format!("../{}/", slug).into());
+        }
+        Ok(root_relative(dest))
+    }
+}
+
+impl Page {
+    /// 读取并解析单个页面；草稿返回 `None`
+    fn load(path: &Path) -> Result<Option<Self>> {
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("读取页面失败: {}", path.display()))?;
+        let (front, body) = split_front_matter(&text);
+        let meta: FrontMatter = match front {
+            Some(yaml) if !yaml.trim().is_empty() => serde_yaml::from_str(yaml)
+                .with_context(|| format!("解析页面 front matter 失败: {}", path.display()))?,
+            _ => FrontMatter::default(),
+        };
+        if meta.draft {
+            return Ok(None);
+        }
+        let stem = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or_default();
+        let slug_src = meta.slug.as_deref().unwrap_or(stem);
+        if !slug_src.chars().any(|c| c.is_ascii_alphanumeric()) {
+            bail!(
+                "页面 {} 无法生成 slug，请在 front matter 中设置 slug",
+                path.display()
+            );
+        }
+        let slug = crate::slugs::slugify(slug_src);
+        if RESERVED_SLUGS.contains(&slug.as_str()) {
+            bail!("页面 slug 与站点目录冲突: {}（{}）", slug, path.display());
+        }
+        let title = meta
+            .title
+            .or_else(|| first_heading(body))
+            .unwrap_or_else(|| stem.to_string());
+        Ok(Some(Self {
+            slug,
+            nav_title: meta.nav_title.unwrap_or_else(|| title.clone()),
+            title,
+            description: meta.description.unwrap_or_default(),
+            template: meta
+                .template
+                .unwrap_or_else(|| DEFAULT_TEMPLATE.to_string()),
+            sitemap: meta.sitemap.unwrap_or(true),
+            lastmod: meta.lastmod,
+            changefreq: meta.changefreq,
+            priority: meta.priority,
+            nav: meta.nav.unwrap_or(true),
+            order: meta.order,
+            source: path.to_path_buf(),
+            body: body.to_string(),
+        }))
+    }
+}
+
+/// 拆分 front matter：文件以 `---` 行开头时，取到下一个 `---` 行为止
+fn split_front_matter(text: &str) -> (Option<&str>, &str) {
+    let text = text.trim_start_matches('\u{feff}');
+    let Some(rest) = text
+        .strip_prefix("---\n")
+        .or_else(|| text.strip_prefix("---\r\n"))
+    else {
+        return (None, text);
+    };
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == "---" {
+            return (Some(&rest[..offset]), &rest[offset + line.len()..]);
+        }
+        offset += line.len();
+    }
+    (None, text)
+}
+
+/// 正文中第一个 `# ` 一级标题
+fn first_heading(body: &str) -> Option<String> {
+    body.lines()
+        .find_map(|l| l.strip_prefix("# "))
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty())
+}
+
+/// 站点根绝对路径（`/assets/a.png`）改为页面相对，保证部署在 base_path 下同样可用
+fn root_relative(dest: CowStr<'_>) -> CowStr<'_> {
+    if dest.starts_with('/') && !dest.starts_with("//") {
+        format!("../{}", dest.trim_start_matches('/')).into()
+    } else {
+        dest
+    }
+}
@@ -0,0 +1,150 @@
+//! Service Worker 预缓存清单模块：
+//! - 扫描构建输出，生成 `{ url, revision }` 列表（含 `go/<slug>/` 页面与下载的图标）
+//! - 按 `site.precache` 的 include/exclude 与总大小上限过滤
+
+use anyhow::{Context, Result};
+use glob::Pattern;
+use serde::Serialize;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    config::PrecacheSettings,
+    fingerprint::{short_hash, AssetManifest},
+};
+
+/// 预缓存条目：`url` 相对站点根（目录页以 `/` 结尾），`revision` 为内容哈希
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct PrecacheEntry {
+    pub(crate) url: String,
+    pub(crate) revision: String,
+    #[serde(skip)]
+    size: u64,
+}
+
+/// 扫描站点目录并生成预缓存清单
+pub(crate) fn collect_precache(
+    site_dir: &Path,
+    settings: Option<&PrecacheSettings>,
+    assets: &AssetManifest,
+) -> Result<Vec<PrecacheEntry>> {
+    let include = compile_patterns(settings.and_then(|s| s.include.as_ref()))?;
+    let exclude = compile_patterns(settings.and_then(|s| s.exclude.as_ref()))?;
+
+    let mut files: Vec<(String, PathBuf)> = Vec::new();
+    walk(site_dir, "", &mut files)?;
+
+    let mut entries: Vec<PrecacheEntry> = Vec::new();
+    for (rel, path) in files {
+        // Service Worker 自身、爬虫文件与部署平台配置无需缓存；已有指纹副本的原文件也跳过
+        if matches!(
+            rel.as_str(),
+            "sw.js"
+                | "assets/sw.js"
+                | "robots.txt"
+                | "sitemap.xml"
+                | "_headers"
+                | "_redirects"
+                | "vercel.json"
+        ) || assets.has_fingerprint(&rel)
+        {
+            continue;
+        }
+        if !include.is_empty() && !include.iter().any(|p| p.matches(&rel)) {
+            continue;
+        }
+        if exclude.iter().any(|p| p.matches(&rel)) {
+            continue;
+        }
+        let bytes = fs::read(&path).with_context(|| format!("读取文件失败: {}", path.display()))?;
+        let url = match rel.strip_suffix("index.html") {
+            Some(dir) if dir.is_empty() || dir.ends_with('/') => dir.to_string(),
+            _ => rel.clone(),
+        };
+        entries.push(PrecacheEntry {
+            url,
+            revision: short_hash(&bytes),
+            size: bytes.len() as u64,
+        });
+    }
+    entries.sort_by(|a, b| rank(&a.url).cmp(&rank(&b.url)).then(a.url.cmp(&b.url)));
+
+    // 总大小上限：按优先级依次放入，放不下的跳过
+    if let Some(limit_kb) = settings.and_then(|s| s.max_total_kb) {
+        let limit = limit_kb.saturating_mul(1024);
+        let mut total: u64 = 0;
+        let mut skipped: Vec<String> = Vec::new();
+        entries.retain(|e| {
+            if total + e.size <= limit {
+                total += e.size;
+                true
+            } else {
+                skipped.push(e.url.clone());
+                false
+            }
+        });
+        if !skipped.is_empty() {
+            eprintln!(
+                "警告: 预缓存超过上限 {} KB，跳过 {} 个文件: {}",
+                limit_kb,
+                skipped.len(),
+                skipped.join(", ")
+            );
+        }
+    }
+    let total: u64 = entries.iter().map(|e| e.size).sum();
+    println!(
+        "🗂️ 预缓存清单: {} 个文件，共 {} KB",
+        entries.len(),
+        total.div_ceil(1024)
+    );
+    Ok(entries)
+}
+
+/// 优先级：首页/内网页/离线页 > CSS/JS > 其他页面 > 其他资源
+fn rank(url: &str) -> u8 {
+    if url.is_empty() || url == "intranet/" || url == "assets/offline.html" {
+        0
+    } else if url.ends_with(".css") || url.ends_with(".js") {
+        1
+    } else if url.ends_with('/') || url.ends_with(".html") {
+        2
+    } else {
+        3
+    }
+}
+
+fn compile_patterns(list: Option<&Vec<String>>) -> Result<Vec<Pattern>> {
+    let mut out = Vec::new();
+    for raw in list.into_iter().flatten() {
+        let pat = raw.trim().trim_start_matches('/');
+        if pat.is_empty() {
+            continue;
+        }
+        out.push(Pattern::new(pat).with_context(|| format!("无效的 precache 路径模式: {}", raw))?);
+    }
+    Ok(out)
+}
+
+fn walk(dir: &Path, rel: &str, out: &mut Vec<(String, PathBuf)>) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.starts_with('.') {
+            continue;
+        }
+        let rel_path = if rel.is_empty() {
+            name
+        } else {
+            format!("{}/{}", rel, name)
+        };
+        if entry.file_type()?.is_dir() {
+            walk(&entry.path(), &rel_path, out)?;
+        } else {
+            out.push((rel_path, entry.path()));
+        }
+    }
+    Ok(())
+}
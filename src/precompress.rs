@@ -0,0 +1,108 @@
+//! 预压缩模块：
+//! - 为站点中的 HTML/CSS/JS/SVG/JSON 生成 `.gz` 与 `.br` 同级文件
+//! - 供 nginx `gzip_static`/`brotli_static` 或支持预压缩的 CDN 直接使用
+//! - 预览服务器复用同样的压缩函数
+
+use anyhow::{Context, Result};
+use std::{fs, io::Write, path::Path};
+
+/// 默认预压缩阈值（字节）：过小的文件压缩收益有限
+pub(crate) const DEFAULT_MIN_SIZE: u64 = 1024;
+
+/// 是否为可预压缩的文本类文件
+pub(crate) fn is_compressible(path: &Path) -> bool {
+    matches!(
+        path.extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("")
+            .to_ascii_lowercase()
+            .as_str(),
+        "html" | "css" | "js" | "mjs" | "svg" | "json"
+    )
+}
+
+/// gzip 压缩（最高压缩级别）
+pub(crate) fn gzip_bytes(data: &[u8]) -> Result<Vec<u8>> {
+    let mut enc = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::best());
+    enc.write_all(data)?;
+    Ok(enc.finish()?)
+}
+
+/// brotli 压缩（quality 11，窗口 22）
+pub(crate) fn brotli_bytes(data: &[u8]) -> Result<Vec<u8>> {
+    let mut out = Vec::new();
+    {
+        let mut enc = brotli::CompressorWriter::new(&mut out, 4096, 11, 22);
+        enc.write_all(data)?;
+        enc.flush()?;
+    }
+    Ok(out)
+}
+
+#[derive(Default)]
+struct Totals {
+    files: usize,
+    original: u64,
+    gzip: u64,
+    brotli: u64,
+}
+
+/// 递归为站点目录中达到阈值的文本文件写出 `.gz`/`.br`
+pub(crate) fn precompress_site(site_dir: &Path, min_size: u64) -> Result<()> {
+    let mut totals = Totals::default();
+    walk(site_dir, min_size, &mut totals)?;
+    if totals.files == 0 {
+        println!("ℹ️ 预压缩: 没有达到 {} 字节阈值的文件。", min_size);
+        return Ok(());
+    }
+    println!(
+        "📦 预压缩: {} 个文件 {} KB -> gzip {} KB / brotli {} KB",
+        totals.files,
+        totals.original.div_ceil(1024),
+        totals.gzip.div_ceil(1024),
+        totals.brotli.div_ceil(1024)
+    );
+    Ok(())
+}
+
+fn walk(dir: &Path, min_size: u64, totals: &mut Totals) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        if entry.file_type()?.is_dir() {
+            walk(&path, min_size, totals)?;
+            continue;
+        }
+        if !is_compressible(&path) || entry.metadata()?.len() < min_size {
+            continue;
+        }
+        let data = fs::read(&path).with_context(|| format!("读取文件失败: {}", path.display()))?;
+        let gz = gzip_bytes(&data).with_context(|| format!("gzip 失败: {}", path.display()))?;
+        let br = brotli_bytes(&data).with_context(|| format!("brotli 失败: {}", path.display()))?;
+        // 压缩后没有变小的就不写，避免服务端选到更大的文件
+        let gz_smaller = gz.len() < data.len();
+        let br_smaller = br.len() < data.len();
+        if !gz_smaller && !br_smaller {
+            continue;
+        }
+        if gz_smaller {
+            write_sibling(&path, "gz", &gz)?;
+        }
+        if br_smaller {
+            write_sibling(&path, "br", &br)?;
+        }
+        totals.files += 1;
+        totals.original += data.len() as u64;
+        totals.gzip += gz.len().min(data.len()) as u64;
+        totals.brotli += br.len().min(data.len()) as u64;
+    }
+    Ok(())
+}
+
+fn write_sibling(path: &Path, ext: &str, data: &[u8]) -> Result<()> {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".");
+    name.push(ext);
+    fs::write(&name, data)
+        .with_context(|| format!("写入预压缩文件失败: {}", Path::new(&name).display()))
+}
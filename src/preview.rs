@@ -1,31 +1,233 @@
 //! 预览与热重载静态文件服务模块
-//! - 监视主题/静态/本地配置变更并增量重建
-//! - 内置极简 HTTP 静态文件服务器，支持热刷新
+//! - 监视主题/静态/本地配置（含 include）变更并增量重建
+//! - 条件轮询远程配置（URL/Gist）
+//! - 可选监听局域网（打印地址与二维码）与自签名 HTTPS
+//! - 可选模拟网络：把内网主机链接改写到本地替身
+//! - 内置极简 HTTP 静态文件服务器，通过 SSE 推送热刷新
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use notify::{RecommendedWatcher, RecursiveMode, Watcher};
 use std::{
-    fs,
+    collections::HashMap,
     path::{Path, PathBuf},
-    sync::{
-        atomic::{AtomicBool, AtomicU64, Ordering},
-        Arc,
-    },
+    sync::{Arc, Mutex},
     thread,
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 use crate::{
-    build::build,
+    build::{build, resolve_theme},
     config::ColorScheme,
-    config::{describe_source, load_config, Config},
+    config::{describe_source, load_config, Config, LoadedConfig},
+    csp,
+    lan::{lan_ip, print_qr},
+    livereload::{LiveReload, CLIENT_SCRIPT},
+    serve::{header, inject_script, serve_static},
+    simulate::NetworkSimulation,
+    staging::is_staging_path,
+    theme::ThemeChain,
 };
 
+#[cfg(feature = "remote")]
+use crate::config::RemoteValidator;
+#[cfg(feature = "https")]
+use crate::lan::self_signed_cert;
+#[cfg(feature = "remote")]
+use std::collections::HashSet;
+
+/// 最后一次变更后等待的静默时间，合并编辑器连续保存产生的多次事件
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// 远程配置的轮询间隔
+#[cfg(feature = "remote")]
+const REMOTE_POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// 等待重建的变更
+#[derive(Default)]
+struct Pending {
+    paths: Vec<PathBuf>,
+    /// 远程配置发生变化
+    remote: bool,
+    last_change: Option<Instant>,
+}
+
+/// 需要监视的本地路径与需要轮询的远程地址
+struct WatchTargets {
+    paths: Vec<(PathBuf, RecursiveMode)>,
+    urls: Vec<String>,
+}
+
+/// 文件监视器及当前已监视的路径（按需增删）
+struct Watches {
+    watcher: RecommendedWatcher,
+    active: HashMap<PathBuf, RecursiveMode>,
+}
+
+impl Watches {
+    /// 同步监视范围：新增缺少的路径，移除不再需要的路径
+    fn sync(&mut self, wanted: &[(PathBuf, RecursiveMode)]) {
+        let wanted: HashMap<PathBuf, RecursiveMode> = wanted.iter().cloned().collect();
+        let stale: Vec<PathBuf> = self
+            .active
+            .iter()
+            .filter(|(p, mode)| wanted.get(*p) != Some(*mode))
+            .map(|(p, _)| p.clone())
+            .collect();
+        for p in stale {
+            let _ = self.watcher.unwatch(&p);
+            self.active.remove(&p);
+        }
+        for (p, mode) in wanted {
+            if self.active.contains_key(&p) {
+                continue;
+            }
+            match self.watcher.watch(&p, mode) {
+                Ok(()) => {
+                    println!("👀 监视: {}", p.display());
+                    self.active.insert(p, mode);
+                }
+                Err(e) => eprintln!("警告: 无法监视 {}: {}", p.display(), e),
+            }
+        }
+    }
+}
+
+/// 预览期间重建所需的全部参数
+struct PreviewBuild {
+    input: Option<PathBuf>,
+    input_url: Option<String>,
+    gist_id: Option<String>,
+    gist_file: Option<String>,
+    token: Option<String>,
+    auth_scheme: Option<String>,
+    out: PathBuf,
+    static_dir: Option<PathBuf>,
+    theme_dir: Option<PathBuf>,
+    base_path: Option<String>,
+    no_intranet: bool,
+    generate_intermediate_page: bool,
+    color_scheme: Option<ColorScheme>,
+    title: Option<String>,
+    desc: Option<String>,
+    build_version: Option<String>,
+    icon_dir: Option<String>,
+    icon_threads: Option<usize>,
+    minify: Option<bool>,
+    precompress: Option<bool>,
+}
+
+impl PreviewBuild {
+    /// 加载并解析配置
+    fn load(&self) -> Result<(LoadedConfig, Config)> {
+        let loaded = load_config(
+            self.input.as_deref(),
+            self.input_url.as_deref(),
+            self.gist_id.as_deref(),
+            self.gist_file.as_deref(),
+            self.token.as_deref(),
+            self.auth_scheme.as_deref(),
+        )?;
+        let cfg: Config = serde_yaml::from_str(&loaded.text)
+            .with_context(|| format!("解析 YAML 失败: {}", describe_source(&loaded.source)))?;
+        Ok((loaded, cfg))
+    }
+
+    fn build(&self, cfg: Config, config_dir: Option<&Path>) -> Result<()> {
+        build(
+            cfg,
+            config_dir,
+            &self.out,
+            self.static_dir.as_deref(),
+            self.theme_dir.as_deref(),
+            self.base_path.clone(),
+            self.no_intranet,
+            self.generate_intermediate_page,
+            self.color_scheme,
+            self.title.clone(),
+            self.desc.clone(),
+            self.build_version.clone(),
+            self.icon_dir.clone(),
+            self.icon_threads,
+            self.minify,
+            self.precompress,
+        )
+    }
+
+    /// 计算监视范围：主题目录（含 `site.theme_dir`、默认主题与继承链上的父主题）、静态目录、
+    /// 配置所在目录递归监视；目录之外的 include 文件监视其所在目录
+    fn targets(&self, loaded: Option<(&LoadedConfig, &Config)>) -> WatchTargets {
+        let mut recursive: Vec<PathBuf> = Vec::new();
+        // 继承链上的父主题同样监视（内置主题无需监视）；清单有误时至少监视当前主题
+        let theme = match loaded {
+            Some((_, cfg)) => resolve_theme(self.theme_dir.as_deref(), cfg),
+            None => match self.theme_dir.as_deref() {
+                Some(dir) => ThemeChain::resolve(dir),
+                None => ThemeChain::embedded(),
+            },
+        };
+        match theme {
+            Ok(chain) => recursive.extend(
+                chain
+                    .layers()
+                    .iter()
+                    .filter_map(|l| l.dir().map(Path::to_path_buf)),
+            ),
+            Err(_) => recursive.extend(self.theme_dir.clone()),
+        }
+        recursive.extend(self.static_dir.clone());
+        // 自定义页面目录（未配置时为 `pages`）
+        recursive.push(PathBuf::from(
+            loaded
+                .and_then(|(_, cfg)| cfg.site.pages.as_ref())
+                .and_then(|p| p.dir.as_deref())
+                .unwrap_or("pages"),
+        ));
+        if let Some(ip) = self.input.as_ref() {
+            recursive.push(if ip.is_dir() {
+                ip.clone()
+            } else {
+                // 相对文件名（如 `dove.yaml`）的父目录为空路径，按当前目录处理
+                parent_dir(ip)
+            });
+        }
+        let mut paths: Vec<(PathBuf, RecursiveMode)> = Vec::new();
+        for p in recursive {
+            if let Ok(abs) = std::path::absolute(&p) {
+                if abs.exists() && !paths.iter().any(|(q, _)| abs.starts_with(q)) {
+                    paths.retain(|(q, _)| !q.starts_with(&abs));
+                    paths.push((abs, RecursiveMode::Recursive));
+                }
+            }
+        }
+        let mut urls = Vec::new();
+        if let Some((loaded, _)) = loaded {
+            for f in &loaded.files {
+                let Ok(dir) = std::path::absolute(parent_dir(f)) else {
+                    continue;
+                };
+                if dir.exists() && !paths.iter().any(|(q, _)| dir.starts_with(q)) {
+                    paths.push((dir, RecursiveMode::NonRecursive));
+                }
+            }
+            urls = loaded.urls.clone();
+        }
+        WatchTargets { paths, urls }
+    }
+}
+
+fn parent_dir(p: &Path) -> PathBuf {
+    match p.parent() {
+        Some(d) if !d.as_os_str().is_empty() => d.to_path_buf(),
+        _ => PathBuf::from("."),
+    }
+}
+
 /// 监视并服务指定目录，按需重建与热刷新
 #[allow(clippy::too_many_arguments)]
 pub(crate) fn preview_watch_and_serve(
     root: PathBuf,
     addr: String,
+    mount: String,
     input: Option<PathBuf>,
     input_url: Option<String>,
     gist_id: Option<String>,
@@ -45,202 +247,315 @@ pub(crate) fn preview_watch_and_serve(
     build_version: Option<String>,
     icon_dir: Option<String>,
     icon_threads: Option<usize>,
+    minify: Option<bool>,
+    precompress: Option<bool>,
+    lan: bool,
+    https: bool,
+    simulate_network: Option<PathBuf>,
 ) -> Result<()> {
     if !root.exists() {
         anyhow::bail!("预览目录不存在: {}", root.display());
     }
+    let simulation = simulate_network
+        .as_deref()
+        .map(NetworkSimulation::load)
+        .transpose()?;
+    let scheme = if https { "https" } else { "http" };
+    let port = addr.rsplit_once(':').map(|(_, p)| p).unwrap_or("8787");
+    let local_url = if lan {
+        format!("{}://127.0.0.1:{}{}", scheme, port, mount)
+    } else {
+        format!("{}://{}{}", scheme, addr, mount)
+    };
     println!("🔎 预览目录: {}", root.display());
-    println!("🚀 访问: http://{}", addr);
+    println!("🚀 访问: {}", local_url);
+    let lan_host = if lan {
+        lan_ip().map(|ip| ip.to_string())
+    } else {
+        None
+    };
+    if lan {
+        match lan_host.as_deref() {
+            Some(host) => {
+                let url = format!("{}://{}:{}{}", scheme, host, port, mount);
+                println!("📱 局域网访问: {}", url);
+                print_qr(&url);
+            }
+            None => eprintln!(
+                "警告: 未能探测到局域网 IP，请使用本机 IP 与端口 {} 访问",
+                port
+            ),
+        }
+    }
+    if let Some(sim) = simulation.as_ref() {
+        sim.describe();
+    }
     if open {
-        let _ = webbrowser::open(&format!("http://{}", addr));
+        let _ = webbrowser::open(&local_url);
     }
 
-    // 版本号与变更标记
-    let version = Arc::new(AtomicU64::new(0));
-    let dirty = Arc::new(AtomicBool::new(false));
+    // 待处理的变更与热刷新中心（含重建状态）
+    let pending: Arc<Mutex<Pending>> = Arc::new(Mutex::new(Pending::default()));
+    let live = Arc::new(LiveReload::default());
+    let job = PreviewBuild {
+        input,
+        input_url,
+        gist_id,
+        gist_file,
+        token,
+        auth_scheme,
+        out,
+        static_dir,
+        theme_dir,
+        base_path,
+        no_intranet,
+        generate_intermediate_page,
+        color_scheme,
+        title,
+        desc,
+        build_version,
+        icon_dir,
+        icon_threads,
+        minify,
+        precompress,
+    };
+    #[cfg(feature = "remote")]
+    let (poll_token, poll_auth_scheme) = (job.token.clone(), job.auth_scheme.clone());
 
-    // 监视（主题目录、静态目录、本地配置文件）
-    {
-        let dirty = dirty.clone();
-        let mut watcher: RecommendedWatcher =
+    // 监视（主题目录、静态目录、本地配置文件及其 include）
+    let watches = {
+        let pending = pending.clone();
+        // 构建输出（含暂存目录）位于监视目录内时忽略其变更，避免重建触发重建；
+        // 构建写回的锁文件（dove.lock）同理
+        let out_abs = std::path::absolute(&job.out).unwrap_or_else(|_| job.out.clone());
+        let watcher: RecommendedWatcher =
             notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
-                if res.is_ok() {
-                    dirty.store(true, Ordering::SeqCst);
+                if let Ok(event) = res {
+                    let paths: Vec<PathBuf> = event
+                        .paths
+                        .into_iter()
+                        .filter(|p| {
+                            !p.starts_with(&out_abs)
+                                && !is_staging_path(p)
+                                && p.extension().is_none_or(|e| e != "lock")
+                        })
+                        .collect();
+                    if !paths.is_empty() {
+                        let mut pending = pending.lock().unwrap();
+                        pending.paths.extend(paths);
+                        pending.last_change = Some(Instant::now());
+                    }
                 }
             })?;
-        if let Some(td) = theme_dir.as_ref() {
-            if td.exists() {
-                watcher.watch(td, RecursiveMode::Recursive)?;
-            }
-        }
-        if let Some(sd) = static_dir.as_ref() {
-            if sd.exists() {
-                watcher.watch(sd, RecursiveMode::Recursive)?;
-            }
+        Watches {
+            watcher,
+            active: HashMap::new(),
         }
-        if let Some(ip) = input.as_ref() {
-            if ip.exists() {
-                let watch_target = if ip.is_dir() {
-                    ip.clone()
-                } else {
-                    ip.parent().unwrap_or(Path::new(".")).to_path_buf()
-                };
-                if watch_target.exists() {
-                    watcher.watch(&watch_target, RecursiveMode::Recursive)?;
+    };
+    // 远程配置地址（随每次加载更新，由轮询线程读取）
+    let remote_urls: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(Vec::new()));
+    {
+        let mut watches = watches;
+        let targets = match job.load() {
+            Ok((loaded, cfg)) => job.targets(Some((&loaded, &cfg))),
+            Err(_) => job.targets(None),
+        };
+        watches.sync(&targets.paths);
+        *remote_urls.lock().unwrap() = targets.urls;
+
+        // 后台重建线程：变更静默 DEBOUNCE 后再重建，连续保存只触发一次；
+        // 重建期间的新变更累积到下一轮，不阻塞监视与服务
+        let pending = pending.clone();
+        let live = live.clone();
+        let remote_urls = remote_urls.clone();
+        thread::spawn(move || loop {
+            thread::sleep(Duration::from_millis(100));
+            let changed = {
+                let mut pending = pending.lock().unwrap();
+                match pending.last_change {
+                    Some(t) if t.elapsed() >= DEBOUNCE => std::mem::take(&mut *pending),
+                    _ => continue,
+                }
+            };
+            // 仅样式表变化时页面可原地替换样式
+            let css_only = !changed.remote
+                && changed
+                    .paths
+                    .iter()
+                    .all(|p| p.extension().is_some_and(|e| e.eq_ignore_ascii_case("css")));
+            live.build_started();
+            let started = Instant::now();
+            let result = job.load().and_then(|(loaded, cfg)| {
+                // 配置变化可能带来新的 include 或主题目录，先更新监视范围
+                let targets = job.targets(Some((&loaded, &cfg)));
+                watches.sync(&targets.paths);
+                *remote_urls.lock().unwrap() = targets.urls;
+                job.build(cfg, loaded.source.local_dir().as_deref())?;
+                Ok(describe_source(&loaded.source))
+            });
+            match result {
+                Ok(source) => {
+                    let version = live.build_succeeded(css_only, source.clone(), started.elapsed());
+                    println!("🔁 已重建，version = {} · 配置来源: {}", version, source);
+                }
+                Err(e) => {
+                    eprintln!("❌ 重建失败（保留上一次的输出）: {:#}", e);
+                    live.build_failed(format!("{:#}", e), started.elapsed());
                 }
             }
-        }
-        // 保持 watcher 活到生命周期末尾
-        std::mem::forget(watcher);
+        });
     }
 
-    // 后台重建线程
+    // 远程配置轮询线程：条件请求（ETag/Last-Modified），内容变化时触发重建
+    #[cfg(feature = "remote")]
     {
-        let version = version.clone();
-        let dirty = dirty.clone();
-        let build_version = build_version.clone();
-        let icon_dir = icon_dir.clone();
+        let pending = pending.clone();
+        let remote_urls = remote_urls.clone();
         thread::spawn(move || {
+            let mut validators: HashMap<String, RemoteValidator> = HashMap::new();
+            let mut failing: HashSet<String> = HashSet::new();
             loop {
-                thread::sleep(Duration::from_millis(400));
-                if dirty.swap(false, Ordering::SeqCst) {
-                    // 重新加载配置并构建
-                    if let Ok(loaded) = load_config(
-                        input.as_deref(),
-                        input_url.as_deref(),
-                        gist_id.as_deref(),
-                        gist_file.as_deref(),
-                        token.as_deref(),
-                        auth_scheme.as_deref(),
-                    ) {
-                        if let Ok(cfg) = serde_yaml::from_str::<Config>(&loaded.text) {
-                            let _ = build(
-                                cfg,
-                                &out,
-                                static_dir.as_deref(),
-                                theme_dir.as_deref(),
-                                base_path.clone(),
-                                no_intranet,
-                                generate_intermediate_page,
-                                color_scheme,
-                                title.clone(),
-                                desc.clone(),
-                                build_version.clone(),
-                                icon_dir.clone(),
-                                icon_threads,
-                            );
-                            version.fetch_add(1, Ordering::SeqCst);
-                            println!(
-                                "🔁 已重建，version = {} · 配置来源: {}",
-                                version.load(Ordering::SeqCst),
-                                describe_source(&loaded.source)
-                            );
+                let urls = remote_urls.lock().unwrap().clone();
+                validators.retain(|u, _| urls.contains(u));
+                for url in urls {
+                    let validator = validators.entry(url.clone()).or_default();
+                    match validator.poll(&url, poll_token.as_deref(), poll_auth_scheme.as_deref()) {
+                        Ok(changed) => {
+                            failing.remove(&url);
+                            if changed {
+                                println!("🌐 远程配置已更新: {}", url);
+                                let mut pending = pending.lock().unwrap();
+                                pending.remote = true;
+                                pending.last_change = Some(Instant::now());
+                            }
+                        }
+                        Err(e) => {
+                            // 同一地址连续失败只提示一次
+                            if failing.insert(url.clone()) {
+                                eprintln!("警告: 轮询远程配置失败: {:#}", e);
+                            }
                         }
                     }
                 }
+                thread::sleep(REMOTE_POLL_INTERVAL);
             }
         });
     }
 
     // 启动服务
-    serve_with_reload(&root, &addr, version)
+    let server = bind_server(&addr, https, lan_host.as_deref())?;
+    serve_with_reload(server, &root, &mount, live, simulation)
 }
 
-fn serve_with_reload(root: &Path, addr: &str, version: Arc<AtomicU64>) -> Result<()> {
-    let server = tiny_http::Server::http(addr)
-        .map_err(|e| anyhow::anyhow!("绑定地址失败: {}: {}", addr, e))?;
-    for rq in server.incoming_requests() {
-        let url = rq.url();
-        if url == "/__dove__/version" {
-            let body = version.load(Ordering::SeqCst).to_string();
-            let _ = rq.respond(tiny_http::Response::from_string(body).with_status_code(200));
-            continue;
-        }
-        let path_only = url.split('?').next().unwrap_or("/");
-        let mut segs = Vec::new();
-        for s in path_only.split('/') {
-            let t = s.trim();
-            if t.is_empty() || t == "." || t == ".." {
-                continue;
-            }
-            segs.push(t);
-        }
-        let mut fpath = root.to_path_buf();
-        for s in &segs {
-            fpath.push(s);
-        }
-        let is_dir_req = path_only.ends_with('/') || segs.is_empty();
-        if is_dir_req {
-            fpath.push("index.html");
-        }
-        let mut status = 200;
-        if !fpath.exists() || fpath.is_dir() {
-            status = 404;
-        }
-        let content_type = content_type_for_path(&fpath);
-        let resp = if status == 200 {
-            if content_type.starts_with("text/html") {
-                match fs::read_to_string(&fpath) {
-                    Ok(mut s) => {
-                        s.push_str("\n<script>(function(){var c=null;async function t(){try{var r=await fetch('/__dove__/version',{cache:'no-store'});var v=await r.text();if(c===null)c=v;else if(v!==c) location.reload();}catch(e){} setTimeout(t,1000);} t();})();</script>\n");
-                        tiny_http::Response::from_string(s).with_header(
-                            tiny_http::Header::from_bytes(
-                                &b"Content-Type"[..],
-                                content_type.as_bytes(),
-                            )
-                            .unwrap(),
-                        )
-                    }
-                    Err(_) => tiny_http::Response::from_string("Not Found").with_status_code(404),
-                }
-            } else {
-                match fs::read(&fpath) {
-                    Ok(bytes) => tiny_http::Response::from_data(bytes).with_header(
-                        tiny_http::Header::from_bytes(
-                            &b"Content-Type"[..],
-                            content_type.as_bytes(),
-                        )
-                        .unwrap(),
-                    ),
-                    Err(_) => tiny_http::Response::from_string("Not Found").with_status_code(404),
+/// 预览服务的工作线程数：按 CPU 数取值，限制在 2..=8
+fn worker_count() -> usize {
+    thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(4)
+        .clamp(2, 8)
+}
+
+/// 创建 HTTP 服务；`https` 时使用启动时生成的自签名证书
+fn bind_server(addr: &str, https: bool, lan_host: Option<&str>) -> Result<tiny_http::Server> {
+    if !https {
+        return tiny_http::Server::http(addr)
+            .map_err(|e| anyhow::anyhow!("绑定地址失败: {}: {}", addr, e));
+    }
+    #[cfg(feature = "https")]
+    {
+        let hosts: Vec<String> = lan_host.into_iter().map(str::to_string).collect();
+        let (certificate, private_key) = self_signed_cert(&hosts)?;
+        println!("🔐 已生成自签名证书（首次访问需在浏览器中信任该证书）");
+        tiny_http::Server::https(
+            addr,
+            tiny_http::SslConfig {
+                certificate,
+                private_key,
+            },
+        )
+        .map_err(|e| anyhow::anyhow!("绑定地址失败: {}: {}", addr, e))
+    }
+    #[cfg(not(feature = "https"))]
+    {
+        let _ = lan_host;
+        anyhow::bail!("HTTPS 预览需要以 `--features https` 构建")
+    }
+}
+
+fn serve_with_reload(
+    server: tiny_http::Server,
+    root: &Path,
+    mount: &str,
+    live: Arc<LiveReload>,
+    simulation: Option<NetworkSimulation>,
+) -> Result<()> {
+    let simulation = Arc::new(simulation);
+    let server = Arc::new(server);
+    // 多个工作线程共享同一监听器并发处理请求，大文件与慢连接不会阻塞其他请求
+    let workers: Vec<_> = (0..worker_count())
+        .map(|_| {
+            let server = server.clone();
+            let root = root.to_path_buf();
+            let mount = mount.to_string();
+            let live = live.clone();
+            let simulation = simulation.clone();
+            thread::spawn(move || {
+                for rq in server.incoming_requests() {
+                    handle_request(rq, &root, &mount, &live, simulation.as_ref().as_ref());
                 }
-            }
-        } else {
-            tiny_http::Response::from_string("Not Found")
-        };
-        let _ = rq.respond(resp.with_status_code(status));
+            })
+        })
+        .collect();
+    for worker in workers {
+        let _ = worker.join();
     }
     Ok(())
 }
 
-fn content_type_for_path(p: &Path) -> String {
-    match p
-        .extension()
-        .and_then(|e| e.to_str())
-        .unwrap_or("")
-        .to_ascii_lowercase()
-        .as_str()
-    {
-        "html" => "text/html; charset=utf-8".into(),
-        "css" => "text/css; charset=utf-8".into(),
-        "js" => "application/javascript; charset=utf-8".into(),
-        "mjs" => "application/javascript; charset=utf-8".into(),
-        "map" => "application/json; charset=utf-8".into(),
-        "json" => "application/json; charset=utf-8".into(),
-        "txt" => "text/plain; charset=utf-8".into(),
-        "svg" => "image/svg+xml".into(),
-        "png" => "image/png".into(),
-        "jpg" | "jpeg" => "image/jpeg".into(),
-        "gif" => "image/gif".into(),
-        "webp" => "image/webp".into(),
-        "avif" => "image/avif".into(),
-        "ico" => "image/x-icon".into(),
-        "woff" => "font/woff".into(),
-        "woff2" => "font/woff2".into(),
-        "ttf" => "font/ttf".into(),
-        "otf" => "font/otf".into(),
-        "eot" => "application/vnd.ms-fontobject".into(),
-        "wasm" => "application/wasm".into(),
-        _ => "application/octet-stream".into(),
+fn handle_request(
+    rq: tiny_http::Request,
+    root: &Path,
+    mount: &str,
+    live: &Arc<LiveReload>,
+    simulation: Option<&NetworkSimulation>,
+) {
+    // 网络模拟：目录替身由 `/__dove__/sim/<host>/` 提供
+    let rq = match simulation {
+        Some(sim) => match sim.serve(rq) {
+            Some(rq) => rq,
+            None => return,
+        },
+        None => rq,
+    };
+    match rq.url() {
+        "/__dove__/events" => live.serve_events(rq),
+        "/__dove__/version" => {
+            let body = live.version().to_string();
+            let _ = rq.respond(
+                tiny_http::Response::from_string(body)
+                    .with_header(header("Cache-Control", "no-store")),
+            );
+        }
+        "/__dove__/status" => {
+            let _ = rq.respond(
+                tiny_http::Response::from_string(live.status_json())
+                    .with_header(header("Content-Type", "application/json; charset=utf-8"))
+                    .with_header(header("Cache-Control", "no-store")),
+            );
+        }
+        _ => {
+            let filter = |html: String| {
+                let html = match simulation {
+                    Some(sim) => sim.rewrite_html(html),
+                    None => html,
+                };
+                // 页面带 CSP meta 时同步放行热刷新脚本
+                inject_script(
+                    csp::allow_preview_script(html, CLIENT_SCRIPT),
+                    CLIENT_SCRIPT,
+                )
+            };
+            serve_static(rq, root, mount, Some(&filter))
+        }
     }
 }
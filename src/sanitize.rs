@@ -0,0 +1,354 @@
+//! HTML 清理模块（构建期）：
+//! - 按允许列表（标签/属性/URL 协议）清理链接详情与简介中的 HTML（ammonia）
+//! - 两档内置策略：`default`（常用排版与图片）与 `strict`（纯排版，远程来源的链接默认使用）
+//! - 列出被移除的标签、属性与链接协议，供构建时告警
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use crate::config::{Link, SanitizeProfile, SanitizeSettings};
+
+/// strict 档：纯排版标签，不含图片、表单与嵌入内容
+const STRICT_TAGS: &[&str] = &[
+    "a",
+    "abbr",
+    "b",
+    "blockquote",
+    "br",
+    "code",
+    "dd",
+    "del",
+    "dl",
+    "dt",
+    "em",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "hr",
+    "i",
+    "kbd",
+    "li",
+    "ol",
+    "p",
+    "pre",
+    "s",
+    "span",
+    "strong",
+    "sub",
+    "sup",
+    "table",
+    "tbody",
+    "td",
+    "th",
+    "thead",
+    "tr",
+    "ul",
+];
+/// default 档在 strict 之上额外允许的标签
+const DEFAULT_EXTRA_TAGS: &[&str] = &[
+    "caption",
+    "cite",
+    "col",
+    "colgroup",
+    "details",
+    "div",
+    "figcaption",
+    "figure",
+    "img",
+    "input",
+    "ins",
+    "mark",
+    "q",
+    "small",
+    "summary",
+    "tfoot",
+    "u",
+];
+const STRICT_TAG_ATTRS: &[(&str, &[&str])] = &[
+    ("a", &["href", "title"]),
+    ("abbr", &["title"]),
+    // 代码高亮输出的 CSS 类
+    ("code", &["class"]),
+    ("pre", &["class"]),
+    ("span", &["class"]),
+    ("td", &["colspan", "rowspan"]),
+    ("th", &["colspan", "rowspan"]),
+    ("ol", &["start"]),
+];
+const DEFAULT_EXTRA_TAG_ATTRS: &[(&str, &[&str])] = &[
+    // 链接统一由 ammonia 加上 rel="noopener noreferrer"，允许新窗口打开
+    ("a", &["target"]),
+    ("img", &["src", "alt", "title", "width", "height"]),
+    ("input", &["type", "checked", "disabled"]),
+    ("details", &["open"]),
+    ("col", &["span"]),
+    ("colgroup", &["span"]),
+];
+const STRICT_GENERIC_ATTRS: &[&str] = &["lang", "title"];
+const DEFAULT_GENERIC_ATTRS: &[&str] = &["class", "lang", "title"];
+const URL_SCHEMES: &[&str] = &["http", "https", "mailto"];
+/// 这些标签连同内容一起移除，不可加入允许列表
+const CONTENT_TAGS: &[&str] = &["script", "style"];
+/// 含 URL 的属性，需检查协议
+const URL_ATTRS: &[&str] = &["href", "src", "cite", "action", "formaction", "poster"];
+
+/// 一套允许列表
+#[derive(Debug, Clone)]
+pub(crate) struct Sanitizer {
+    tags: HashSet<String>,
+    tag_attrs: HashMap<String, HashSet<String>>,
+    generic_attrs: HashSet<String>,
+}
+
+impl Sanitizer {
+    fn profile(profile: SanitizeProfile) -> Self {
+        let mut tags: HashSet<String> = STRICT_TAGS.iter().map(|s| s.to_string()).collect();
+        let mut tag_attrs: HashMap<String, HashSet<String>> = HashMap::new();
+        let mut add_attrs = |list: &[(&str, &[&str])]| {
+            for (tag, attrs) in list {
+                tag_attrs
+                    .entry(tag.to_string())
+                    .or_default()
+                    .extend(attrs.iter().map(|a| a.to_string()));
+            }
+        };
+        add_attrs(STRICT_TAG_ATTRS);
+        let generic: &[&str] = match profile {
+            SanitizeProfile::Strict => STRICT_GENERIC_ATTRS,
+            SanitizeProfile::Default => {
+                tags.extend(DEFAULT_EXTRA_TAGS.iter().map(|s| s.to_string()));
+                add_attrs(DEFAULT_EXTRA_TAG_ATTRS);
+                DEFAULT_GENERIC_ATTRS
+            }
+        };
+        Self {
+            tags,
+            tag_attrs,
+            generic_attrs: generic.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    /// 在内置档位上追加 `allow_tags` / `allow_attributes`（`*` 表示所有标签）
+    fn extend(&mut self, settings: &SanitizeSettings) {
+        for tag in &settings.allow_tags {
+            let tag = tag.trim().to_ascii_lowercase();
+            if CONTENT_TAGS.contains(&tag.as_str()) {
+                eprintln!("警告: site.sanitize.allow_tags 不支持 <{}>，已忽略", tag);
+                continue;
+            }
+            self.tags.insert(tag);
+        }
+        for (tag, attrs) in &settings.allow_attributes {
+            let tag = tag.trim().to_ascii_lowercase();
+            if CONTENT_TAGS.contains(&tag.as_str()) {
+                eprintln!(
+                    "警告: site.sanitize.allow_attributes 不支持 <{}>，已忽略",
+                    tag
+                );
+                continue;
+            }
+            let attrs = attrs
+                .iter()
+                .map(|a| a.trim().to_ascii_lowercase())
+                .filter(|a| {
+                    // `<a rel>` 固定为 noopener noreferrer，不能放行
+                    let fixed = a == "rel" && (tag == "*" || tag == "a");
+                    if fixed {
+                        eprintln!(
+                            "警告: site.sanitize.allow_attributes 不支持 {} 的 rel 属性（链接统一使用 noopener noreferrer），已忽略",
+                            tag
+                        );
+                    }
+                    !fixed
+                })
+                .collect::<Vec<_>>();
+            if tag == "*" {
+                self.generic_attrs.extend(attrs);
+            } else {
+                self.tag_attrs.entry(tag).or_default().extend(attrs);
+            }
+        }
+    }
+
+    /// 清理 HTML，返回 (清理结果, 被移除的内容描述)
+    pub(crate) fn clean(&self, html: &str) -> (String, Vec<String>) {
+        let tags: HashSet<&str> = self.tags.iter().map(String::as_str).collect();
+        let tag_attrs: HashMap<&str, HashSet<&str>> = self
+            .tag_attrs
+            .iter()
+            .map(|(t, a)| (t.as_str(), a.iter().map(String::as_str).collect()))
+            .collect();
+        let generic: HashSet<&str> = self.generic_attrs.iter().map(String::as_str).collect();
+        let cleaned = ammonia::Builder::default()
+            .tags(tags)
+            .tag_attributes(tag_attrs)
+            .generic_attributes(generic)
+            .url_schemes(URL_SCHEMES.iter().copied().collect())
+            .clean(html)
+            .to_string();
+        (cleaned, self.stripped(html))
+    }
+
+    fn attr_allowed(&self, tag: &str, attr: &str) -> bool {
+        self.generic_attrs.contains(attr)
+            || self.tag_attrs.get(tag).is_some_and(|a| a.contains(attr))
+    }
+
+    /// 粗略扫描原始 HTML，列出不在允许列表中的标签、属性与链接协议
+    fn stripped(&self, html: &str) -> Vec<String> {
+        let mut out: BTreeSet<String> = BTreeSet::new();
+        for tag in scan_tags(html) {
+            if !self.tags.contains(&tag.name) {
+                out.insert(format!("<{}>", tag.name));
+                continue;
+            }
+            for (attr, value) in tag.attrs {
+                // `<a rel>` 总会被替换为 noopener noreferrer，无需告警
+                if tag.name == "a" && attr == "rel" {
+                    continue;
+                }
+                if !self.attr_allowed(&tag.name, &attr) {
+                    out.insert(format!("{}（<{}>）", attr, tag.name));
+                } else if URL_ATTRS.contains(&attr.as_str()) {
+                    if let Some(scheme) = url_scheme(&value) {
+                        if !URL_SCHEMES.contains(&scheme.as_str()) {
+                            out.insert(format!("{}: 链接（<{}>）", scheme, tag.name));
+                        }
+                    }
+                }
+            }
+        }
+        out.into_iter().collect()
+    }
+}
+
+/// 站点的清理策略：本地链接与远程来源链接各一套
+#[derive(Debug, Clone)]
+pub(crate) struct Sanitizers {
+    local: Sanitizer,
+    remote: Sanitizer,
+    /// `site.sanitize.trusted: true`：完全关闭清理
+    trusted: bool,
+}
+
+impl Sanitizers {
+    pub(crate) fn new(settings: Option<&SanitizeSettings>) -> Self {
+        let defaults = SanitizeSettings::default();
+        let s = settings.unwrap_or(&defaults);
+        let mut local = Sanitizer::profile(s.profile.unwrap_or(SanitizeProfile::Default));
+        local.extend(s);
+        let remote = Sanitizer::profile(s.remote_profile.unwrap_or(SanitizeProfile::Strict));
+        Self {
+            local,
+            remote,
+            trusted: s.trusted,
+        }
+    }
+
+    /// 链接适用的策略；`None` 表示可信、不做清理。
+    /// 远程来源的链接忽略自身的 `trusted`，避免远程片段自行放开限制
+    pub(crate) fn for_link(&self, l: &Link) -> Option<&Sanitizer> {
+        if self.trusted {
+            return None;
+        }
+        if l.remote_source {
+            if l.trusted {
+                eprintln!(
+                    "警告: 链接 {} 来自远程配置，忽略其 trusted: true（可在主配置的 site.sanitize 中调整）",
+                    l.name
+                );
+            }
+            return Some(&self.remote);
+        }
+        if l.trusted {
+            None
+        } else {
+            Some(&self.local)
+        }
+    }
+}
+
+struct ScannedTag {
+    name: String,
+    attrs: Vec<(String, String)>,
+}
+
+/// 扫描开始标签及其属性（忽略结束标签、注释与声明）
+fn scan_tags(html: &str) -> Vec<ScannedTag> {
+    let bytes = html.as_bytes();
+    let mut tags = Vec::new();
+    let mut i = 0;
+    while let Some(pos) = html[i..].find('<') {
+        i += pos + 1;
+        if !bytes.get(i).is_some_and(u8::is_ascii_alphabetic) {
+            continue;
+        }
+        let start = i;
+        while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'-') {
+            i += 1;
+        }
+        let name = html[start..i].to_ascii_lowercase();
+        let mut attrs = Vec::new();
+        loop {
+            while i < bytes.len() && (bytes[i].is_ascii_whitespace() || bytes[i] == b'/') {
+                i += 1;
+            }
+            if i >= bytes.len() || bytes[i] == b'>' {
+                break;
+            }
+            let a_start = i;
+            while i < bytes.len()
+                && !bytes[i].is_ascii_whitespace()
+                && !matches!(bytes[i], b'=' | b'>' | b'/')
+            {
+                i += 1;
+            }
+            let attr = html[a_start..i].to_ascii_lowercase();
+            while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+                i += 1;
+            }
+            let mut value = String::new();
+            if bytes.get(i) == Some(&b'=') {
+                i += 1;
+                while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+                    i += 1;
+                }
+                match bytes.get(i) {
+                    Some(&q) if q == b'"' || q == b'\'' => {
+                        let v_start = i + 1;
+                        let v_end = html[v_start..]
+                            .find(q as char)
+                            .map_or(bytes.len(), |p| v_start + p);
+                        value = html[v_start..v_end].to_string();
+                        i = (v_end + 1).min(bytes.len());
+                    }
+                    _ => {
+                        let v_start = i;
+                        while i < bytes.len() && !bytes[i].is_ascii_whitespace() && bytes[i] != b'>'
+                        {
+                            i += 1;
+                        }
+                        value = html[v_start..i].to_string();
+                    }
+                }
+            }
+            if !attr.is_empty() {
+                attrs.push((attr, value));
+            }
+        }
+        tags.push(ScannedTag { name, attrs });
+    }
+    tags
+}
+
+/// URL 的协议（小写）；相对地址返回 None
+fn url_scheme(url: &str) -> Option<String> {
+    let url: String = url.chars().filter(|c| !c.is_ascii_whitespace()).collect();
+    let colon = url.find(':')?;
+    if url[..colon].contains(['/', '?', '#']) {
+        return None;
+    }
+    Some(url[..colon].to_ascii_lowercase())
+}
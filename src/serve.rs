@@ -0,0 +1,417 @@
+//! 静态文件服务模块（供 preview 使用）：
+//! - 站点挂载在 `/<base_path>/` 下（与部署一致），`/` 重定向到挂载点
+//! - GET/HEAD，目录请求补全 `index.html`，缺少结尾斜杠时 301 重定向
+//! - `ETag`/`Last-Modified` 与条件请求（304）
+//! - 单段 `Range` 请求（206/416），大文件流式读取
+//! - 站点根 `404.html` 作为自定义 404 页面
+//! - 按 `Accept-Encoding` 返回 `.br`/`.gz` 预压缩文件
+//! - 始终读取最近一次完整构建的输出（重建替换目录时不会读到半成品）
+
+use std::{
+    fs::{self, File},
+    io::{Cursor, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use chrono::{DateTime, Utc};
+use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS};
+use tiny_http::{Header, Method, Request, Response, StatusCode};
+
+use crate::{
+    precompress::{brotli_bytes, gzip_bytes},
+    staging::read_snapshot,
+};
+
+type Body = Box<dyn Read + Send>;
+
+/// HTML 页面改写函数
+pub(crate) type HtmlFilter<'a> = &'a (dyn Fn(String) -> String + Sync);
+
+/// 处理一次静态文件请求
+///
+/// `mount` 为站点挂载路径（如 `/` 或 `/secretPath/`），`html` 为 HTML 页面的改写函数（如注入热刷新脚本）。
+pub(crate) fn serve_static(rq: Request, root: &Path, mount: &str, html: Option<HtmlFilter>) {
+    // 解析路径与打开文件期间持有快照读锁，响应体在释放锁后再发送
+    let resp = {
+        let _snapshot = read_snapshot();
+        build_response(&rq, root, mount, html)
+    };
+    let _ = rq.respond(resp);
+}
+
+/// 由 base_path 计算挂载路径：`secretPath` -> `/secretPath/`，空则为 `/`
+pub(crate) fn mount_path(base_path: Option<&str>) -> String {
+    let segs: Vec<&str> = base_path
+        .unwrap_or("")
+        .split('/')
+        .map(str::trim)
+        .filter(|t| !t.is_empty() && *t != "." && *t != "..")
+        .collect();
+    if segs.is_empty() {
+        "/".to_string()
+    } else {
+        format!("/{}/", segs.join("/"))
+    }
+}
+
+fn build_response(
+    rq: &Request,
+    root: &Path,
+    mount: &str,
+    html: Option<HtmlFilter>,
+) -> Response<Body> {
+    if !matches!(rq.method(), Method::Get | Method::Head) {
+        return text_response(405, "Method Not Allowed").with_header(header("Allow", "GET, HEAD"));
+    }
+    let url = rq.url();
+    let (path_only, query) = match url.split_once('?') {
+        Some((p, q)) => (p, Some(q)),
+        None => (url, None),
+    };
+    let with_query = |mut location: String| {
+        if let Some(q) = query {
+            location.push('?');
+            location.push_str(q);
+        }
+        location
+    };
+    // 站点只在挂载点下可见：`/` 跳转到挂载点，挂载点缺少结尾斜杠时补全
+    if mount != "/" {
+        if path_only == "/" {
+            return redirect(302, "Found", &with_query(mount.to_string()));
+        }
+        if path_only == mount.trim_end_matches('/') {
+            return redirect(301, "Moved Permanently", &with_query(mount.to_string()));
+        }
+    }
+    let Some(rel) = path_only.strip_prefix(mount) else {
+        return not_found(rq, root, html);
+    };
+    let decoded = percent_encoding::percent_decode_str(rel).decode_utf8_lossy();
+    let mut segs = Vec::new();
+    for s in decoded.split('/') {
+        let t = s.trim();
+        if t.is_empty() || t == "." || t == ".." {
+            continue;
+        }
+        segs.push(t);
+    }
+    let mut fpath = root.to_path_buf();
+    for s in &segs {
+        fpath.push(s);
+    }
+    if fpath.is_dir() {
+        if !path_only.ends_with('/') {
+            // 与静态托管一致：/go/foo -> /go/foo/
+            // 由挂载点与规范化后的路径段重建，避免 `//host` 之类的协议相对地址
+            let path: Vec<String> = segs
+                .iter()
+                .map(|s| utf8_percent_encode(s, PATH_SEGMENT).to_string())
+                .collect();
+            let location = with_query(format!("{}{}/", mount, path.join("/")));
+            return redirect(301, "Moved Permanently", &location);
+        }
+        fpath.push("index.html");
+    }
+    if !fpath.is_file() {
+        return not_found(rq, root, html);
+    }
+    serve_file(rq, &fpath, 200, html)
+}
+
+fn not_found(rq: &Request, root: &Path, html: Option<HtmlFilter>) -> Response<Body> {
+    let custom = root.join("404.html");
+    if custom.is_file() {
+        return serve_file(rq, &custom, 404, html);
+    }
+    text_response(404, "Not Found")
+}
+
+fn serve_file(rq: &Request, fpath: &Path, status: u16, html: Option<HtmlFilter>) -> Response<Body> {
+    let Ok(meta) = fs::metadata(fpath) else {
+        return text_response(404, "Not Found");
+    };
+    let content_type = content_type_for_path(fpath);
+    let is_html = content_type.starts_with("text/html");
+    let accept_encoding = request_header(rq, "Accept-Encoding")
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    // 存在 .br/.gz 预压缩文件且客户端接受时，以对应编码返回
+    let encoding = negotiate_encoding(&accept_encoding, fpath);
+    let modified = meta.modified().ok();
+    let last_modified = modified.map(http_date);
+    let etag = format!(
+        "\"{:x}-{:x}{}{}\"",
+        meta.len(),
+        modified
+            .and_then(|m| m.duration_since(SystemTime::UNIX_EPOCH).ok())
+            .map(|d| d.as_nanos())
+            .unwrap_or(0),
+        if is_html && html.is_some() { "-i" } else { "" },
+        encoding.map(|e| format!("-{}", e)).unwrap_or_default()
+    );
+
+    let mut headers = vec![
+        header("Content-Type", &content_type),
+        header("Cache-Control", "no-cache"),
+        header("Vary", "Accept-Encoding"),
+    ];
+    if status == 200 {
+        headers.push(header("ETag", &etag));
+        if let Some(lm) = last_modified.as_deref() {
+            headers.push(header("Last-Modified", lm));
+        }
+        if not_modified(rq, &etag, modified) {
+            return respond(304, headers, Box::new(std::io::empty()), Some(0));
+        }
+    }
+
+    // HTML 需要注入脚本：读入内存并按协商编码即时压缩
+    if is_html {
+        let Ok(mut s) = fs::read_to_string(fpath) else {
+            return text_response(404, "Not Found");
+        };
+        if let Some(filter) = html {
+            s = filter(s);
+        }
+        let body = match encoding.map(|enc| (enc, compress(enc, s.as_bytes()))) {
+            Some((enc, Ok(data))) => {
+                headers.push(header("Content-Encoding", enc));
+                data
+            }
+            _ => s.into_bytes(),
+        };
+        let len = body.len();
+        return respond(status, headers, Box::new(Cursor::new(body)), Some(len));
+    }
+
+    // 预压缩文件整体返回（不支持 Range）
+    if let Some(enc) = encoding {
+        let cpath = precompressed_path(fpath, enc);
+        if let (Ok(file), Ok(cmeta)) = (File::open(&cpath), fs::metadata(&cpath)) {
+            headers.push(header("Content-Encoding", enc));
+            return respond(status, headers, Box::new(file), Some(cmeta.len() as usize));
+        }
+    }
+
+    let Ok(mut file) = File::open(fpath) else {
+        return text_response(404, "Not Found");
+    };
+    let total = meta.len();
+    headers.push(header("Accept-Ranges", "bytes"));
+    if status == 200 {
+        if let Some(range) = request_header(rq, "Range") {
+            if if_range_matches(rq, &etag, last_modified.as_deref()) {
+                match parse_range(&range, total) {
+                    Some((start, end)) => {
+                        if file.seek(SeekFrom::Start(start)).is_ok() {
+                            let len = end - start + 1;
+                            headers.push(header(
+                                "Content-Range",
+                                &format!("bytes {}-{}/{}", start, end, total),
+                            ));
+                            return respond(
+                                206,
+                                headers,
+                                Box::new(file.take(len)),
+                                Some(len as usize),
+                            );
+                        }
+                    }
+                    None => {
+                        headers.push(header("Content-Range", &format!("bytes */{}", total)));
+                        return respond(416, headers, Box::new(std::io::empty()), Some(0));
+                    }
+                }
+            }
+        }
+    }
+    respond(status, headers, Box::new(file), Some(total as usize))
+}
+
+/// 将脚本插入到 `</body>` 之前；没有 `</body>` 时追加到末尾
+pub(crate) fn inject_script(mut html: String, script: &str) -> String {
+    match html.rfind("</body>") {
+        Some(idx) => html.insert_str(idx, script),
+        None => html.push_str(script),
+    }
+    html
+}
+
+fn not_modified(rq: &Request, etag: &str, modified: Option<SystemTime>) -> bool {
+    if let Some(inm) = request_header(rq, "If-None-Match") {
+        // If-None-Match 优先于 If-Modified-Since
+        return inm
+            .split(',')
+            .map(|t| t.trim().trim_start_matches("W/"))
+            .any(|t| t == "*" || t == etag);
+    }
+    if let (Some(ims), Some(m)) = (request_header(rq, "If-Modified-Since"), modified) {
+        if let Ok(since) = DateTime::parse_from_rfc2822(&ims) {
+            let m: DateTime<Utc> = m.into();
+            return m.timestamp() <= since.timestamp();
+        }
+    }
+    false
+}
+
+fn if_range_matches(rq: &Request, etag: &str, last_modified: Option<&str>) -> bool {
+    match request_header(rq, "If-Range") {
+        None => true,
+        Some(v) => {
+            let v = v.trim();
+            v == etag || Some(v) == last_modified
+        }
+    }
+}
+
+/// 解析单段字节范围：`bytes=a-b` / `bytes=a-` / `bytes=-n`；不可满足时返回 None
+fn parse_range(value: &str, total: u64) -> Option<(u64, u64)> {
+    let spec = value.trim().strip_prefix("bytes=")?;
+    // 多段范围只取第一段
+    let first = spec.split(',').next()?.trim();
+    let (a, b) = first.split_once('-')?;
+    let (a, b) = (a.trim(), b.trim());
+    if total == 0 {
+        return None;
+    }
+    let (start, end) = if a.is_empty() {
+        let n: u64 = b.parse().ok()?;
+        if n == 0 {
+            return None;
+        }
+        (total.saturating_sub(n), total - 1)
+    } else {
+        let start: u64 = a.parse().ok()?;
+        let end: u64 = if b.is_empty() {
+            total - 1
+        } else {
+            b.parse::<u64>().ok()?.min(total - 1)
+        };
+        (start, end)
+    };
+    if start > end || start >= total {
+        return None;
+    }
+    Some((start, end))
+}
+
+/// 按 Accept-Encoding 选择可用的预压缩文件：优先 br，其次 gzip
+fn negotiate_encoding(accept: &str, fpath: &Path) -> Option<&'static str> {
+    let accepts = |name: &str| {
+        accept.split(',').any(|part| {
+            let mut it = part.split(';');
+            let token = it.next().unwrap_or("").trim();
+            let rejected = it.any(|p| {
+                let p = p.trim().replace(' ', "");
+                p == "q=0" || p == "q=0.0" || p == "q=0.00" || p == "q=0.000"
+            });
+            token == name && !rejected
+        })
+    };
+    ["br", "gzip"]
+        .into_iter()
+        .find(|enc| accepts(enc) && precompressed_path(fpath, enc).is_file())
+}
+
+fn precompressed_path(fpath: &Path, encoding: &str) -> PathBuf {
+    let mut name = fpath.as_os_str().to_owned();
+    name.push(if encoding == "br" { ".br" } else { ".gz" });
+    PathBuf::from(name)
+}
+
+fn compress(encoding: &str, data: &[u8]) -> anyhow::Result<Vec<u8>> {
+    if encoding == "br" {
+        brotli_bytes(data)
+    } else {
+        gzip_bytes(data)
+    }
+}
+
+fn http_date(t: SystemTime) -> String {
+    let dt: DateTime<Utc> = t.into();
+    dt.format("%a, %d %b %Y %H:%M:%S GMT").to_string()
+}
+
+fn request_header(rq: &Request, name: &str) -> Option<String> {
+    rq.headers()
+        .iter()
+        .find(|h| h.field.as_str().as_str().eq_ignore_ascii_case(name))
+        .map(|h| h.value.as_str().to_string())
+}
+
+/// 路径段中需要编码的字符
+const PATH_SEGMENT: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'<')
+    .add(b'>')
+    .add(b'?')
+    .add(b'`')
+    .add(b'{')
+    .add(b'}');
+
+/// 跳转响应：Location 来自请求路径，非 ASCII 与控制字符按百分号编码；仍无法作为响应头时返回 400
+fn redirect(status: u16, text: &str, location: &str) -> Response<Body> {
+    let location = utf8_percent_encode(location, CONTROLS).to_string();
+    match Header::from_bytes(&b"Location"[..], location.as_bytes()) {
+        Ok(h) => text_response(status, text).with_header(h),
+        Err(_) => text_response(400, "Bad Request"),
+    }
+}
+
+pub(crate) fn header(name: &str, value: &str) -> Header {
+    Header::from_bytes(name.as_bytes(), value.as_bytes()).unwrap()
+}
+
+fn respond(status: u16, headers: Vec<Header>, body: Body, len: Option<usize>) -> Response<Body> {
+    // 始终带上 Content-Length（HEAD 与 Range 响应需要），不切换为 chunked
+    Response::new(StatusCode(status), headers, body, len, None).with_chunked_threshold(usize::MAX)
+}
+
+pub(crate) fn text_response(status: u16, text: &str) -> Response<Body> {
+    let body = text.as_bytes().to_vec();
+    let len = body.len();
+    respond(
+        status,
+        vec![header("Content-Type", "text/plain; charset=utf-8")],
+        Box::new(Cursor::new(body)),
+        Some(len),
+    )
+}
+
+pub(crate) fn content_type_for_path(p: &Path) -> String {
+    match p
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_ascii_lowercase()
+        .as_str()
+    {
+        "html" => "text/html; charset=utf-8".into(),
+        "css" => "text/css; charset=utf-8".into(),
+        "js" => "application/javascript; charset=utf-8".into(),
+        "mjs" => "application/javascript; charset=utf-8".into(),
+        "map" => "application/json; charset=utf-8".into(),
+        "json" => "application/json; charset=utf-8".into(),
+        "txt" => "text/plain; charset=utf-8".into(),
+        "xml" => "application/xml; charset=utf-8".into(),
+        "svg" => "image/svg+xml".into(),
+        "png" => "image/png".into(),
+        "jpg" | "jpeg" => "image/jpeg".into(),
+        "gif" => "image/gif".into(),
+        "webp" => "image/webp".into(),
+        "avif" => "image/avif".into(),
+        "ico" => "image/x-icon".into(),
+        "woff" => "font/woff".into(),
+        "woff2" => "font/woff2".into(),
+        "ttf" => "font/ttf".into(),
+        "otf" => "font/otf".into(),
+        "eot" => "application/vnd.ms-fontobject".into(),
+        "wasm" => "application/wasm".into(),
+        _ => "application/octet-stream".into(),
+    }
+}
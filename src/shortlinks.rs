@@ -0,0 +1,62 @@
+//! 短链接模块：
+//! - 为外网链接生成 `s/<slug>`（slug 与 `go/<slug>/` 一致），以及 `aliases` 中的别名（按 `site.slug_strategy` 转换）
+//! - 短链接直接跳转目标地址，不经过中间页
+//! - 输出为最小跳转页（meta refresh）和/或部署平台的重定向规则（见 deploy）
+
+use std::collections::HashSet;
+
+use crate::{
+    config::SlugStrategy,
+    deploy::Redirect,
+    slugs::{slugify_with, unique_slug},
+};
+
+/// 短链接目录缺省值
+pub(crate) const DEFAULT_DIR: &str = "s";
+
+/// 参与短链接的外网链接
+pub(crate) struct ShortLink<'a> {
+    pub(crate) name: &'a str,
+    pub(crate) slug: &'a str,
+    /// 目标地址（已附加 UTM 参数）
+    pub(crate) url: &'a str,
+    pub(crate) aliases: &'a [String],
+}
+
+/// 计算全部短链接（`from` 为 `<dir>/<slug>/`）；先占用主 slug，再按链接顺序分配别名
+pub(crate) fn collect(dir: &str, links: &[ShortLink], strategy: SlugStrategy) -> Vec<Redirect> {
+    let mut used: HashSet<String> = links.iter().map(|l| l.slug.to_string()).collect();
+    let mut out: Vec<Redirect> = links
+        .iter()
+        .map(|l| Redirect {
+            from: format!("{}/{}/", dir, l.slug),
+            to: l.url.to_string(),
+        })
+        .collect();
+    for l in links {
+        for alias in l.aliases {
+            let Some(wanted) = slugify_with(alias, strategy) else {
+                eprintln!(
+                    "警告: 链接 {} 的别名 {} 没有可用字符（site.slug_strategy），已忽略",
+                    l.name, alias
+                );
+                continue;
+            };
+            if wanted == l.slug {
+                continue;
+            }
+            let slug = unique_slug(&wanted, &mut used);
+            if slug != wanted {
+                eprintln!(
+                    "警告: 链接 {} 的别名 {} 已被占用，改用 {}/{}",
+                    l.name, alias, dir, slug
+                );
+            }
+            out.push(Redirect {
+                from: format!("{}/{}/", dir, slug),
+                to: l.url.to_string(),
+            });
+        }
+    }
+    out
+}
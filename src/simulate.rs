@@ -0,0 +1,152 @@
+//! 网络模拟模块（供 preview 使用）：
+//! - 从映射文件读取“内网主机 -> 本地替身”，无需 VPN 即可点测内网链接
+//! - 改写页面中指向这些主机的链接（含中间页的跳转地址）
+//! - 替身为 URL 时直接改写为该地址；为目录时改写为 `/__dove__/sim/<host>/...`，由预览服务提供
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+use tiny_http::Request;
+
+use crate::serve::serve_static;
+
+/// 目录替身在预览服务中的挂载前缀
+const SIM_PREFIX: &str = "/__dove__/sim/";
+
+/// 映射文件格式
+#[derive(Debug, Deserialize)]
+struct MappingFile {
+    /// 主机（可带端口）-> 替身（`http(s)://` 开头为 URL，否则为相对映射文件的目录）
+    #[serde(default)]
+    hosts: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone)]
+enum StandIn {
+    Url(String),
+    Dir(PathBuf),
+}
+
+/// 网络模拟配置
+#[derive(Debug, Clone, Default)]
+pub(crate) struct NetworkSimulation {
+    hosts: Vec<(String, StandIn)>,
+}
+
+impl NetworkSimulation {
+    /// 读取映射文件
+    pub(crate) fn load(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("读取网络模拟映射失败: {}", path.display()))?;
+        let file: MappingFile = serde_yaml::from_str(&text)
+            .with_context(|| format!("解析网络模拟映射失败: {}", path.display()))?;
+        let base = path.parent().unwrap_or(Path::new("."));
+        let mut hosts = Vec::new();
+        for (host, target) in file.hosts {
+            let host = host.trim().trim_end_matches('/').to_ascii_lowercase();
+            let target = target.trim();
+            if host.is_empty() || target.is_empty() {
+                continue;
+            }
+            let stand_in = if target.starts_with("http://") || target.starts_with("https://") {
+                StandIn::Url(target.trim_end_matches('/').to_string())
+            } else {
+                let dir = base.join(target);
+                if !dir.is_dir() {
+                    anyhow::bail!("网络模拟替身目录不存在: {} -> {}", host, dir.display());
+                }
+                StandIn::Dir(dir)
+            };
+            hosts.push((host, stand_in));
+        }
+        // 长主机名优先，避免 `a.corp` 抢先匹配 `a.corp:8080`
+        hosts.sort_by_key(|(h, _)| std::cmp::Reverse(h.len()));
+        Ok(Self { hosts })
+    }
+
+    /// 打印映射摘要
+    pub(crate) fn describe(&self) {
+        for (host, stand_in) in &self.hosts {
+            match stand_in {
+                StandIn::Url(u) => println!("🧪 模拟网络: {} -> {}", host, u),
+                StandIn::Dir(d) => println!(
+                    "🧪 模拟网络: {} -> {}（{}{}/）",
+                    host,
+                    d.display(),
+                    SIM_PREFIX,
+                    host
+                ),
+            }
+        }
+    }
+
+    /// 改写 HTML 中指向映射主机的 `http(s)://host` 前缀（同时处理 Tera 转义后的 `&#x2F;`）
+    pub(crate) fn rewrite_html(&self, html: String) -> String {
+        let mut out = html;
+        for (host, stand_in) in &self.hosts {
+            let replacement = match stand_in {
+                StandIn::Url(u) => u.clone(),
+                StandIn::Dir(_) => format!("{}{}", SIM_PREFIX, host),
+            };
+            for scheme in ["http:", "https:"] {
+                for sep in ["//", "&#x2F;&#x2F;"] {
+                    let needle = format!("{}{}{}", scheme, sep, host).to_ascii_lowercase();
+                    out = replace_host(&out, &needle, &replacement);
+                }
+            }
+        }
+        out
+    }
+
+    /// 处理 `/__dove__/sim/<host>/...` 请求；不属于目录替身的请求原样交还
+    pub(crate) fn serve(&self, rq: Request) -> Option<Request> {
+        let Some(rest) = rq.url().strip_prefix(SIM_PREFIX) else {
+            return Some(rq);
+        };
+        let host = rest
+            .split(['/', '?'])
+            .next()
+            .unwrap_or("")
+            .to_ascii_lowercase();
+        let dir = self.hosts.iter().find_map(|(h, s)| match s {
+            StandIn::Dir(d) if *h == host => Some(d.clone()),
+            _ => None,
+        });
+        match dir {
+            Some(dir) => {
+                let mount = format!("{}{}/", SIM_PREFIX, host);
+                serve_static(rq, &dir, &mount, None);
+                None
+            }
+            None => Some(rq),
+        }
+    }
+}
+
+/// 替换 `needle`，要求其后紧跟非主机名字符（避免 `a.corp` 误改 `a.corporate`）
+fn replace_host(text: &str, needle: &str, replacement: &str) -> String {
+    let lower = text.to_ascii_lowercase();
+    let mut out = String::with_capacity(text.len());
+    let mut last = 0;
+    let mut from = 0;
+    while let Some(pos) = lower[from..].find(needle) {
+        let start = from + pos;
+        let end = start + needle.len();
+        let boundary = lower[end..]
+            .chars()
+            .next()
+            .is_none_or(|c| !(c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | ':')));
+        if boundary {
+            out.push_str(&text[last..start]);
+            out.push_str(replacement);
+            last = end;
+        }
+        from = end;
+    }
+    out.push_str(&text[last..]);
+    out
+}
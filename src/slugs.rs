@@ -0,0 +1,373 @@
+//! 链接 slug 模块：
+//! - 由名称生成 slug，名称重复时改用 name+host，仍冲突时追加序号（`unique_slug`）
+//! - `site.slug_strategy`：ascii / transliterate（`pinyin` 为其别名）/ unicode（地址中百分号编码）/ hash（URL 短哈希）；
+//!   策略记录在锁文件中，变更后按新策略重新生成并保留旧 slug 用于重定向
+//! - 构建前为全部外网链接分配 `go/<slug>/`：生成中间页的链接在前，其余链接随后
+//! - 锁文件 `dove.lock` 按 URL 记录已分配的 slug，调整链接顺序不会改变已发布的路径；
+//!   slug 变化时告警，并保留旧 slug 用于重定向
+
+use anyhow::{Context, Result};
+use deunicode::deunicode_char;
+use percent_encoding::{utf8_percent_encode, CONTROLS};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    config::{Config, Link, SlugStrategy},
+    utils::{fnv1a64, hostname_from_url},
+};
+
+/// 锁文件缺省路径（相对本地配置所在目录）
+const DEFAULT_LOCK_FILE: &str = "dove.lock";
+
+/// 锁文件
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+struct LockFile {
+    /// 生成这些 slug 时的 `site.slug_strategy`（早期锁文件没有记录，视为与当前配置一致）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    strategy: Option<SlugStrategy>,
+    /// 链接 URL -> slug
+    #[serde(default)]
+    links: BTreeMap<String, LockedSlug>,
+}
+
+/// 锁文件中的一条链接记录
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct LockedSlug {
+    slug: String,
+    /// 曾经使用过的 slug（保留以便重定向，不会分配给其它链接）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    retired: Vec<String>,
+}
+
+/// 已停用的 slug 及其当前 slug
+#[derive(Debug, Clone)]
+pub(crate) struct RetiredSlug {
+    pub(crate) old: String,
+    pub(crate) current: String,
+}
+
+/// slug 分配结果
+#[derive(Debug, Default)]
+pub(crate) struct SlugAssignment {
+    /// 按 `[分组][链接]`；无外网地址的链接为 None
+    slugs: Vec<Vec<Option<String>>>,
+    retired: Vec<RetiredSlug>,
+    /// 启用锁文件时：(路径, 更新后的内容, 是否有变化)
+    lock: Option<(PathBuf, LockFile, bool)>,
+}
+
+impl SlugAssignment {
+    pub(crate) fn get(&self, group: usize, link: usize) -> Option<&str> {
+        self.slugs
+            .get(group)
+            .and_then(|g| g.get(link))
+            .and_then(|s| s.as_deref())
+    }
+
+    /// 需要重定向的旧 slug（`site.slug_lock.redirect_retired: false` 时为空）
+    pub(crate) fn retired(&self) -> &[RetiredSlug] {
+        &self.retired
+    }
+
+    /// 构建成功后写回锁文件（无变化时不写）
+    pub(crate) fn save_lock(&self) -> Result<()> {
+        let Some((path, lock, true)) = &self.lock else {
+            return Ok(());
+        };
+        let text = serde_yaml::to_string(lock).context("序列化 slug 锁文件失败")?;
+        let text = format!(
+            "# 由 dove build 生成：按链接 URL 记录 go/<slug>/，请随配置一起提交\n{}",
+            text
+        );
+        fs::write(path, text)
+            .with_context(|| format!("写入 slug 锁文件失败: {}", path.display()))?;
+        println!("🔒 已更新 slug 锁文件: {}", path.display());
+        Ok(())
+    }
+}
+
+/// 链接的外网地址（去除空白；未配置时为 None）
+fn external_url(l: &Link) -> Option<&str> {
+    l.url.as_deref().map(str::trim).filter(|u| !u.is_empty())
+}
+
+/// 为全部外网链接分配 slug；启用锁文件时优先沿用锁定的 slug
+pub(crate) fn assign_slugs(
+    cfg: &Config,
+    generate_intermediate_page: bool,
+    base_dir: &Path,
+) -> Result<SlugAssignment> {
+    let strategy = cfg.site.slug_strategy;
+    let settings = cfg.site.slug_lock.as_ref();
+    let lock_path = match settings {
+        Some(s) if !s.enabled.unwrap_or(true) => None,
+        Some(s) => Some(base_dir.join(s.path.as_deref().unwrap_or(DEFAULT_LOCK_FILE))),
+        None => Some(base_dir.join(DEFAULT_LOCK_FILE)),
+    };
+    let redirect_retired =
+        lock_path.is_some() && settings.and_then(|s| s.redirect_retired).unwrap_or(true);
+    let old_lock: LockFile = match &lock_path {
+        Some(p) if p.exists() => {
+            let text = fs::read_to_string(p)
+                .with_context(|| format!("读取 slug 锁文件失败: {}", p.display()))?;
+            serde_yaml::from_str(&text)
+                .with_context(|| format!("解析 slug 锁文件失败: {}", p.display()))?
+        }
+        _ => LockFile::default(),
+    };
+
+    // 预留配置中仍存在的链接的锁定 slug（含已停用的），新链接不会占用
+    let present: HashSet<&str> = cfg
+        .groups
+        .iter()
+        .flat_map(|g| g.links.iter().filter_map(external_url))
+        .collect();
+    let mut used: HashSet<String> = HashSet::new();
+    for (url, entry) in &old_lock.links {
+        if present.contains(url.as_str()) {
+            used.insert(entry.slug.clone());
+            used.extend(entry.retired.iter().cloned());
+        }
+    }
+
+    let mut slugs: Vec<Vec<Option<String>>> = cfg
+        .groups
+        .iter()
+        .map(|g| vec![None; g.links.len()])
+        .collect();
+    let mut name_counts: HashMap<String, u32> = HashMap::new();
+    let mut new_lock = LockFile {
+        strategy: Some(strategy),
+        ..LockFile::default()
+    };
+    // 策略变化时按新策略重新生成由名称得到的 slug，旧 slug 转为停用
+    let strategy_changed = old_lock.strategy.is_some_and(|s| s != strategy);
+    let mut rederived = 0;
+    let consequence = if redirect_retired {
+        "，旧地址将重定向到新地址"
+    } else {
+        "，旧地址将失效"
+    };
+    // 生成中间页的链接先分配，与未启用锁文件时的 slug 保持一致
+    for intermediate in [true, false] {
+        for (gi, g) in cfg.groups.iter().enumerate() {
+            for (li, l) in g.links.iter().enumerate() {
+                let Some(url) = external_url(l) else {
+                    continue;
+                };
+                if l.intermediate_page.unwrap_or(generate_intermediate_page) != intermediate {
+                    continue;
+                }
+                let host = hostname_from_url(url).unwrap_or_default();
+                let base = base_slug(l, url, &host, strategy, &mut name_counts);
+                // 同一 URL 出现多次时，仅第一个沿用锁定的 slug
+                let locked = old_lock
+                    .links
+                    .get(url)
+                    .filter(|_| !new_lock.links.contains_key(url));
+                let entry = match locked {
+                    None => LockedSlug {
+                        slug: unique_slug(&base, &mut used),
+                        retired: Vec::new(),
+                    },
+                    // 显式 slug 与锁定值不同（不含去重追加的序号）：以配置为准，旧 slug 转为停用
+                    Some(e)
+                        if (l.slug.is_some() || strategy_changed)
+                            && !derived_from(&e.slug, &base) =>
+                    {
+                        let mut retired = e.retired.clone();
+                        let slug = match retired.iter().position(|r| *r == base) {
+                            Some(i) => retired.remove(i),
+                            None => unique_slug(&base, &mut used),
+                        };
+                        if l.slug.is_some() {
+                            eprintln!(
+                                "警告: 链接 {} 的 slug 由 {} 变为 {}{}",
+                                l.name, e.slug, slug, consequence
+                            );
+                        } else {
+                            rederived += 1;
+                        }
+                        retired.push(e.slug.clone());
+                        LockedSlug { slug, retired }
+                    }
+                    Some(e) => e.clone(),
+                };
+                slugs[gi][li] = Some(entry.slug.clone());
+                if !new_lock.links.contains_key(url) {
+                    new_lock.links.insert(url.to_string(), entry);
+                }
+            }
+        }
+    }
+
+    if rederived > 0 {
+        eprintln!(
+            "警告: site.slug_strategy 已变更，{} 个链接按新策略重新生成 slug{}",
+            rederived, consequence
+        );
+    }
+
+    let retired = if redirect_retired {
+        new_lock
+            .links
+            .values()
+            .flat_map(|e| {
+                e.retired.iter().map(|old| RetiredSlug {
+                    old: old.clone(),
+                    current: e.slug.clone(),
+                })
+            })
+            .collect()
+    } else {
+        Vec::new()
+    };
+    let lock = lock_path.map(|p| {
+        let changed = new_lock != old_lock;
+        (p, new_lock, changed)
+    });
+    Ok(SlugAssignment {
+        slugs,
+        retired,
+        lock,
+    })
+}
+
+/// 页面等仅允许 ASCII 的 slug：保留字母与数字，其余字符折叠为 `-`；结果为空时为 `link`
+pub(crate) fn slugify(input: &str) -> String {
+    let s = slug_text(input, SlugStrategy::Ascii);
+    if s.is_empty() {
+        "link".to_string()
+    } else {
+        s
+    }
+}
+
+/// 按策略转换用户给出的 slug 文本（显式 `slug`、别名）；hash 策略按 transliterate 处理。
+/// 没有可用字符时返回 None
+pub(crate) fn slugify_with(input: &str, strategy: SlugStrategy) -> Option<String> {
+    Some(slug_text(input, strategy)).filter(|s| !s.is_empty())
+}
+
+/// 按策略把文本转为 slug（可能为空）
+fn slug_text(input: &str, strategy: SlugStrategy) -> String {
+    let mut s = String::with_capacity(input.len());
+    let mut prev_dash = false;
+    let mut push = |s: &mut String, c: char| {
+        if c.is_alphanumeric() {
+            s.extend(c.to_lowercase());
+            prev_dash = false;
+        } else if !prev_dash && !s.is_empty() {
+            s.push('-');
+            prev_dash = true;
+        }
+    };
+    for ch in input.chars() {
+        if ch.is_ascii() {
+            push(&mut s, ch);
+            continue;
+        }
+        let transliterate = match strategy {
+            SlugStrategy::Ascii => false,
+            // pinyin 是 transliterate 的别名：转写表中的汉字即为拼音，其它文字同样转写（去除重音等）
+            SlugStrategy::Pinyin | SlugStrategy::Transliterate | SlugStrategy::Hash => true,
+            SlugStrategy::Unicode => {
+                push(&mut s, ch);
+                continue;
+            }
+        };
+        match deunicode_char(ch).filter(|_| transliterate) {
+            Some(ascii) => {
+                // 汉字逐字转写为音节（如 `Bai `），与前后内容以 `-` 分隔
+                if is_han(ch) {
+                    push(&mut s, ' ');
+                }
+                ascii.chars().for_each(|c| push(&mut s, c));
+            }
+            None => push(&mut s, ' '),
+        }
+    }
+    while s.ends_with('-') {
+        s.pop();
+    }
+    s
+}
+
+/// CJK 统一表意文字（含扩展区与兼容区）
+fn is_han(ch: char) -> bool {
+    matches!(
+        ch,
+        '\u{3400}'..='\u{4DBF}'
+            | '\u{4E00}'..='\u{9FFF}'
+            | '\u{F900}'..='\u{FAFF}'
+            | '\u{20000}'..='\u{2FA1F}'
+    )
+}
+
+/// 由链接 URL 计算的短哈希 slug（8 位十六进制）
+fn hash_slug(url: &str) -> String {
+    format!("{:016x}", fnv1a64(url.as_bytes()))[..8].to_string()
+}
+
+/// 站内路径用于链接地址时的形式：非 ASCII 字符按 UTF-8 百分号编码（输出目录仍使用原字符）
+pub(crate) fn encode_path(path: &str) -> String {
+    utf8_percent_encode(path, CONTROLS).to_string()
+}
+
+/// 链接的基础 slug：显式 `slug` 优先；否则按 name 生成，name 重复时使用 name+host 组合；
+/// hash 策略或 name 中没有可用字符时使用 URL 的短哈希
+fn base_slug(
+    l: &Link,
+    url: &str,
+    host: &str,
+    strategy: SlugStrategy,
+    name_counts: &mut HashMap<String, u32>,
+) -> String {
+    if let Some(user_slug) = &l.slug {
+        return slugify_with(user_slug, strategy).unwrap_or_else(|| {
+            eprintln!(
+                "警告: 链接 {} 的 slug {} 没有可用字符，改用 URL 短哈希",
+                l.name, user_slug
+            );
+            hash_slug(url)
+        });
+    }
+    if strategy == SlugStrategy::Hash {
+        return hash_slug(url);
+    }
+    let entry = name_counts.entry(l.name.to_lowercase()).or_insert(0);
+    *entry += 1;
+    let name = slug_text(&l.name, strategy);
+    if name.is_empty() {
+        hash_slug(url)
+    } else if *entry > 1 && !host.is_empty() {
+        slug_text(&format!("{}-{}", l.name, host), strategy)
+    } else {
+        name
+    }
+}
+
+/// `slug` 是否由 `base` 得到：相同，或为 `unique_slug` 追加序号后的 `base-N`
+fn derived_from(slug: &str, base: &str) -> bool {
+    slug == base
+        || slug
+            .strip_prefix(base)
+            .and_then(|rest| rest.strip_prefix('-'))
+            .is_some_and(|n| n.parse::<u32>().is_ok_and(|n| n >= 2))
+}
+
+pub(crate) fn unique_slug(base: &str, used: &mut HashSet<String>) -> String {
+    let mut slug = base.to_string();
+    let mut i = 2;
+    while used.contains(&slug) {
+        slug = format!("{}-{}", base, i);
+        i += 1;
+    }
+    used.insert(slug.clone());
+    slug
+}
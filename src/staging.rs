@@ -0,0 +1,183 @@
+//! 构建暂存目录模块：
+//! - 先渲染到与站点目录同级的临时目录
+//! - 全部成功后再整体替换正式目录；失败时自动清理，保留上一次的输出
+//! - 站点目录之外的文件（如 base_path 时输出根的部署文件）同样先暂存，替换站点目录后再移入
+//! - 替换期间持有快照锁，预览服务据此只读到某一次完整构建的输出
+
+use anyhow::{bail, Context, Result};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::{RwLock, RwLockReadGuard},
+};
+
+/// 站点目录之外文件的暂存目录名（以 `.dove-staging` 结尾，文件监视会忽略）
+const OUTSIDE_DIR: &str = ".dove-files.dove-staging";
+
+/// 替换站点目录时持有写锁；读取站点文件的一方持有读锁，避免看到替换到一半的目录
+static SNAPSHOT_LOCK: RwLock<()> = RwLock::new(());
+
+/// 获取快照读锁：持有期间站点目录不会被替换（已打开的文件在替换后仍可继续读取）
+pub(crate) fn read_snapshot() -> RwLockReadGuard<'static, ()> {
+    SNAPSHOT_LOCK.read().unwrap_or_else(|e| e.into_inner())
+}
+
+/// 站点暂存目录：未提交前被丢弃时会自动删除
+pub(crate) struct StagingDir {
+    staging: PathBuf,
+    target: PathBuf,
+    /// 站点目录之外的文件：(暂存目录, 目标目录)
+    outside: Option<(PathBuf, PathBuf)>,
+    committed: bool,
+}
+
+impl StagingDir {
+    /// 为目标目录创建同级暂存目录（如 `dist/.secretPath.dove-staging`）
+    pub(crate) fn create(target: &Path) -> Result<Self> {
+        let name = target
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .filter(|n| n != "." && n != "..");
+        let Some(name) = name else {
+            bail!(
+                "输出目录无效: {}。请使用独立的输出目录（如 dist）。",
+                target.display()
+            );
+        };
+        if let (Ok(cwd), Ok(abs)) = (std::env::current_dir(), target.canonicalize()) {
+            if cwd.starts_with(&abs) {
+                bail!("输出目录不能是当前目录或其上级目录: {}", target.display());
+            }
+        }
+        let parent = match target.parent() {
+            Some(p) if !p.as_os_str().is_empty() => p.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+        if !parent.exists() {
+            fs::create_dir_all(&parent)
+                .with_context(|| format!("创建输出目录失败: {}", parent.display()))?;
+        }
+        let staging = parent.join(format!(".{}.dove-staging", name));
+        if staging.exists() {
+            // 上次异常退出遗留的暂存目录
+            fs::remove_dir_all(&staging)
+                .with_context(|| format!("清理旧的暂存目录失败: {}", staging.display()))?;
+        }
+        fs::create_dir_all(&staging)
+            .with_context(|| format!("创建暂存目录失败: {}", staging.display()))?;
+        Ok(Self {
+            staging,
+            target: target.to_path_buf(),
+            outside: None,
+            committed: false,
+        })
+    }
+
+    /// 暂存目录路径（构建输出写到这里）
+    pub(crate) fn path(&self) -> &Path {
+        &self.staging
+    }
+
+    /// 暂存写往站点目录之外 `dest` 的文件（仅支持一个目标目录）；提交时在替换站点目录后逐个移入
+    pub(crate) fn outside_dir(&mut self, dest: &Path) -> Result<PathBuf> {
+        if let Some((dir, existing)) = &self.outside {
+            if existing != dest {
+                bail!("暂存目录外文件仅支持一个目标目录: {}", dest.display());
+            }
+            return Ok(dir.clone());
+        }
+        let dir = dest.join(OUTSIDE_DIR);
+        if dir.exists() {
+            fs::remove_dir_all(&dir)
+                .with_context(|| format!("清理旧的暂存目录失败: {}", dir.display()))?;
+        }
+        fs::create_dir_all(&dir).with_context(|| format!("创建暂存目录失败: {}", dir.display()))?;
+        self.outside = Some((dir.clone(), dest.to_path_buf()));
+        Ok(dir)
+    }
+
+    /// 用暂存目录替换正式目录：旧目录先移到备份位置，替换成功后再删除
+    pub(crate) fn commit(mut self) -> Result<()> {
+        let name = self
+            .target
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let backup = self.staging.with_file_name(format!(".{}.dove-old", name));
+        if backup.exists() {
+            fs::remove_dir_all(&backup)
+                .with_context(|| format!("清理旧的备份目录失败: {}", backup.display()))?;
+        }
+        let swap = SNAPSHOT_LOCK.write().unwrap_or_else(|e| e.into_inner());
+        let had_target = self.target.exists();
+        if had_target {
+            fs::rename(&self.target, &backup).with_context(|| {
+                format!(
+                    "移动旧输出失败: {} -> {}",
+                    self.target.display(),
+                    backup.display()
+                )
+            })?;
+        }
+        if let Err(e) = fs::rename(&self.staging, &self.target) {
+            if had_target {
+                // 尽力恢复旧输出
+                let _ = fs::rename(&backup, &self.target);
+            }
+            bail!(
+                "替换输出目录失败: {} -> {}: {}",
+                self.staging.display(),
+                self.target.display(),
+                e
+            );
+        }
+        self.committed = true;
+        if let Some((dir, dest)) = &self.outside {
+            for entry in
+                fs::read_dir(dir).with_context(|| format!("读取暂存目录失败: {}", dir.display()))?
+            {
+                let from = entry?.path();
+                let Some(name) = from.file_name() else {
+                    continue;
+                };
+                let to = dest.join(name);
+                // Windows 上 rename 不覆盖已有文件
+                if cfg!(windows) && to.is_file() {
+                    fs::remove_file(&to)?;
+                }
+                fs::rename(&from, &to).with_context(|| {
+                    format!("移动文件失败: {} -> {}", from.display(), to.display())
+                })?;
+            }
+            let _ = fs::remove_dir_all(dir);
+        }
+        drop(swap);
+        if had_target {
+            if let Err(e) = fs::remove_dir_all(&backup) {
+                eprintln!("警告: 无法删除旧输出备份 {}: {}", backup.display(), e);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Drop for StagingDir {
+    fn drop(&mut self) {
+        if !self.committed && self.staging.exists() {
+            let _ = fs::remove_dir_all(&self.staging);
+        }
+        if let Some((dir, _)) = &self.outside {
+            if dir.exists() {
+                let _ = fs::remove_dir_all(dir);
+            }
+        }
+    }
+}
+
+/// 路径是否位于构建暂存目录或替换时的备份目录中（供文件监视忽略）
+pub(crate) fn is_staging_path(path: &Path) -> bool {
+    path.components().any(|c| {
+        let name = c.as_os_str().to_string_lossy();
+        name.starts_with('.') && (name.ends_with(".dove-staging") || name.ends_with(".dove-old"))
+    })
+}
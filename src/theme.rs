@@ -0,0 +1,473 @@
+//! 主题模块：
+//! - 读取主题清单 `theme.toml`/`theme.yaml`，按 `extends` 解析继承链（子主题在前）
+//! - 按继承链加载模板：子主题同名模板覆盖父主题；每层模板另以 `<主题名>/<模板>` 注册，
+//!   供 `{% extends "default/index.html.tera" %}` 跨主题继承
+//! - 按继承链叠加复制资源：先父后子，子主题同名文件覆盖父主题
+//! - 合并各层清单 `options` 声明的默认值与 `site.theme_options`，校验后以 `theme` 注入模板
+//! - 磁盘上没有默认主题时，直接使用编译进程序的内置默认主题（模板从内存加载，资源从内存写出）
+
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use serde_yaml::Value;
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+use tera::Tera;
+
+/// 内置默认主题的名称（`extends: default` 在磁盘上找不到时指向它）
+const EMBEDDED_THEME: &str = "default";
+
+/// 主题清单文件名（按顺序查找第一个存在的）
+const MANIFEST_FILES: [&str; 3] = ["theme.toml", "theme.yaml", "theme.yml"];
+
+/// 主题清单
+#[derive(Debug, Clone, Default, Deserialize)]
+pub(crate) struct ThemeManifest {
+    /// 主题名（缺省为目录名）
+    #[serde(default)]
+    pub(crate) name: Option<String>,
+    /// 主题版本（`dove theme` 安装时记录到锁文件）
+    #[serde(default)]
+    pub(crate) version: Option<String>,
+    /// 父主题：主题名（同级目录，如 `default`）或相对本主题目录的路径
+    #[serde(default)]
+    pub(crate) extends: Option<String>,
+    /// 主题选项及其默认值（`site.theme_options` 据此校验）
+    #[serde(default)]
+    pub(crate) options: BTreeMap<String, Value>,
+}
+
+impl ThemeManifest {
+    /// 读取主题目录下的清单；没有清单时返回空清单
+    pub(crate) fn load(theme_dir: &Path) -> Result<Self> {
+        let Some(path) = MANIFEST_FILES
+            .iter()
+            .map(|f| theme_dir.join(f))
+            .find(|p| p.is_file())
+        else {
+            return Ok(Self::default());
+        };
+        let text = fs::read_to_string(&path)
+            .with_context(|| format!("读取主题清单失败: {}", path.display()))?;
+        Self::parse(&text, &path.to_string_lossy())
+    }
+
+    /// 内置默认主题的清单
+    fn embedded() -> Result<Self> {
+        for f in MANIFEST_FILES {
+            if let Some(text) = crate::init::default_theme_file(f).and_then(|f| f.contents_utf8()) {
+                return Self::parse(text, &format!("内置主题 {}", f));
+            }
+        }
+        Ok(Self::default())
+    }
+
+    fn parse(text: &str, origin: &str) -> Result<Self> {
+        if origin.ends_with(".toml") {
+            toml::from_str(text).with_context(|| format!("解析主题清单失败: {}", origin))
+        } else {
+            serde_yaml::from_str(text).with_context(|| format!("解析主题清单失败: {}", origin))
+        }
+    }
+}
+
+/// 主题来源
+#[derive(Debug, Clone)]
+pub(crate) enum ThemeSource {
+    /// 磁盘上的主题目录
+    Dir(PathBuf),
+    /// 编译进程序的默认主题
+    Embedded,
+}
+
+/// 继承链中的一层主题
+#[derive(Debug, Clone)]
+pub(crate) struct ThemeLayer {
+    /// 主题名（清单 `name` 或目录名）
+    pub(crate) name: String,
+    pub(crate) source: ThemeSource,
+    manifest: ThemeManifest,
+}
+
+impl ThemeLayer {
+    /// 磁盘主题目录（内置主题为 None）
+    pub(crate) fn dir(&self) -> Option<&Path> {
+        match &self.source {
+            ThemeSource::Dir(d) => Some(d),
+            ThemeSource::Embedded => None,
+        }
+    }
+
+    /// 本层模板：(模板名, 内容)，模板名以 `/` 分隔且相对 `templates/`
+    fn templates(&self) -> Result<Vec<(String, String)>> {
+        let mut out = Vec::new();
+        match &self.source {
+            ThemeSource::Dir(dir) => {
+                let templates = dir.join("templates");
+                for (path, rel) in template_files(&templates)? {
+                    let text = fs::read_to_string(&path)
+                        .with_context(|| format!("读取模板失败: {}", path.display()))?;
+                    out.push((rel, text));
+                }
+            }
+            ThemeSource::Embedded => {
+                for f in crate::init::default_theme_files() {
+                    let Ok(rel) = f.path().strip_prefix("templates") else {
+                        continue;
+                    };
+                    let Some(text) = f.contents_utf8() else {
+                        continue;
+                    };
+                    out.push((slash_path(rel), text.to_string()));
+                }
+                out.sort();
+            }
+        }
+        Ok(out)
+    }
+
+    /// 将本层 `assets/` 复制到目标目录；返回是否有资源
+    fn copy_assets(&self, dest: &Path) -> Result<bool> {
+        match &self.source {
+            ThemeSource::Dir(dir) => {
+                let assets = dir.join("assets");
+                if !assets.is_dir() {
+                    return Ok(false);
+                }
+                if !dest.exists() {
+                    fs::create_dir_all(dest)?;
+                }
+                crate::init::copy_dir_all(&assets, dest)?;
+                Ok(true)
+            }
+            ThemeSource::Embedded => {
+                let mut copied = false;
+                for f in crate::init::default_theme_files() {
+                    let Ok(rel) = f.path().strip_prefix("assets") else {
+                        continue;
+                    };
+                    let out_path = dest.join(rel);
+                    if let Some(parent) = out_path.parent() {
+                        fs::create_dir_all(parent)?;
+                    }
+                    fs::write(&out_path, f.contents())
+                        .with_context(|| format!("写出内置主题资源失败: {}", out_path.display()))?;
+                    copied = true;
+                }
+                Ok(copied)
+            }
+        }
+    }
+}
+
+/// 主题继承链：第一个为当前主题，之后依次为父主题
+#[derive(Debug, Clone)]
+pub(crate) struct ThemeChain {
+    layers: Vec<ThemeLayer>,
+}
+
+impl ThemeChain {
+    /// 仅包含内置默认主题
+    pub(crate) fn embedded() -> Result<Self> {
+        Ok(Self {
+            layers: vec![ThemeLayer {
+                name: EMBEDDED_THEME.to_string(),
+                source: ThemeSource::Embedded,
+                manifest: ThemeManifest::embedded()?,
+            }],
+        })
+    }
+
+    /// 从主题目录出发，沿 `extends` 解析继承链（检测循环继承）
+    pub(crate) fn resolve(theme_dir: &Path) -> Result<Self> {
+        Self::resolve_as(theme_dir, None)
+    }
+
+    /// 同 [`ThemeChain::resolve`]，入口主题清单未声明 `name` 时以 `name` 代替目录名
+    /// （安装时在暂存目录中校验）
+    pub(crate) fn resolve_as(theme_dir: &Path, name: Option<&str>) -> Result<Self> {
+        let mut entry_name = name.map(str::to_string);
+        let mut layers: Vec<ThemeLayer> = Vec::new();
+        let mut seen: Vec<PathBuf> = Vec::new();
+        let mut dir = theme_dir.to_path_buf();
+        loop {
+            let key = dir.canonicalize().unwrap_or_else(|_| dir.clone());
+            if seen.contains(&key) {
+                let names: Vec<&str> = layers.iter().map(|l| l.name.as_str()).collect();
+                bail!(
+                    "主题继承出现循环: {} -> {}",
+                    names.join(" -> "),
+                    dir.display()
+                );
+            }
+            seen.push(key);
+            let manifest = ThemeManifest::load(&dir)?;
+            let fallback = entry_name.take();
+            let name = manifest.name.clone().or(fallback).unwrap_or_else(|| {
+                dir.file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_else(|| "theme".to_string())
+            });
+            let parent = match manifest.extends.as_deref().map(str::trim) {
+                Some(p) if !p.is_empty() => Some(parent_theme(&dir, p)?),
+                _ => None,
+            };
+            layers.push(ThemeLayer {
+                name,
+                source: ThemeSource::Dir(dir.clone()),
+                manifest,
+            });
+            match parent {
+                Some(ThemeSource::Dir(p)) => dir = p,
+                Some(ThemeSource::Embedded) => {
+                    layers.extend(Self::embedded()?.layers);
+                    break;
+                }
+                None => break,
+            }
+        }
+        // 模板以 `<主题名>/` 为命名空间，同名层会互相覆盖（子主题 extends 自己）
+        for (i, layer) in layers.iter().enumerate() {
+            if layers[..i].iter().any(|l| l.name == layer.name) {
+                let names: Vec<&str> = layers.iter().map(|l| l.name.as_str()).collect();
+                bail!(
+                    "主题继承链中存在同名主题 {}: {}（请在 theme.toml 中为子主题设置不同的 name）",
+                    layer.name,
+                    names.join(" -> ")
+                );
+            }
+        }
+        Ok(Self { layers })
+    }
+
+    /// 各层主题（子主题在前）
+    pub(crate) fn layers(&self) -> &[ThemeLayer] {
+        &self.layers
+    }
+
+    /// 是否用到了内置默认主题
+    pub(crate) fn uses_embedded(&self) -> bool {
+        self.layers
+            .iter()
+            .any(|l| matches!(l.source, ThemeSource::Embedded))
+    }
+
+    /// 继承链描述（如 `my-theme -> default（内置）`）
+    pub(crate) fn describe(&self) -> String {
+        let names: Vec<String> = self
+            .layers
+            .iter()
+            .map(|l| match l.source {
+                ThemeSource::Dir(_) => l.name.clone(),
+                ThemeSource::Embedded => format!("{}（内置）", l.name),
+            })
+            .collect();
+        names.join(" -> ")
+    }
+
+    /// 按继承链加载模板（子主题同名模板覆盖父主题）
+    pub(crate) fn load_templates(&self) -> Result<Tera> {
+        let mut templates: Vec<(String, String)> = Vec::new();
+        // 先父后子：同名模板后登记者生效
+        for layer in self.layers.iter().rev() {
+            for (rel, text) in layer.templates()? {
+                templates.push((format!("{}/{}", layer.name, rel), text.clone()));
+                templates.push((rel, text));
+            }
+        }
+        let mut tera = Tera::default();
+        // 一次性登记，保证跨主题的 `{% extends %}` 在全部模板就绪后再解析
+        tera.add_raw_templates(templates)
+            .with_context(|| format!("加载模板失败（主题: {}）", self.describe()))?;
+        Ok(tera)
+    }
+
+    /// 合并主题选项：各层声明的默认值（子主题覆盖父主题），再用站点配置覆盖。
+    /// 主题声明了选项时，未声明的键告警并忽略，类型与默认值不符时报错；未声明任何选项时原样接受
+    pub(crate) fn options(
+        &self,
+        site_options: &BTreeMap<String, Value>,
+    ) -> Result<BTreeMap<String, Value>> {
+        let mut merged: BTreeMap<String, Value> = BTreeMap::new();
+        for layer in self.layers.iter().rev() {
+            for (k, v) in &layer.manifest.options {
+                merged.insert(k.clone(), v.clone());
+            }
+        }
+        if merged.is_empty() {
+            return Ok(site_options.clone());
+        }
+        let mut errors = Vec::new();
+        for (k, v) in site_options {
+            let Some(default) = merged.get(k) else {
+                let known: Vec<&str> = merged.keys().map(String::as_str).collect();
+                eprintln!(
+                    "警告: site.theme_options.{} 未在主题 {} 中声明，已忽略（可用: {}）",
+                    k,
+                    self.describe(),
+                    known.join(", ")
+                );
+                continue;
+            };
+            let (expected, actual) = (value_kind(default), value_kind(v));
+            if expected != "空" && actual != "空" && expected != actual {
+                errors.push(format!("{} 应为{}，实际为{}", k, expected, actual));
+                continue;
+            }
+            merged.insert(k.clone(), v.clone());
+        }
+        if !errors.is_empty() {
+            bail!("site.theme_options 类型不匹配: {}", errors.join("；"));
+        }
+        // 模板会把这些值直接写入 <style> 或 style 属性，只接受 CSS 颜色/长度/图片地址
+        for (k, v) in &merged {
+            let Value::String(s) = v else {
+                continue;
+            };
+            let s = s.trim();
+            if s.is_empty() {
+                continue;
+            }
+            if k.ends_with("_color") && !is_css_color(s) {
+                errors.push(format!("{} 不是有效的 CSS 颜色: {}", k, s));
+            } else if k.ends_with("_radius") && !is_css_length(s) {
+                errors.push(format!("{} 不是有效的 CSS 长度: {}", k, s));
+            } else if k.ends_with("_image") && !is_css_url(s) {
+                errors.push(format!(
+                    "{} 须为 http(s) 地址或相对路径，且不含引号、括号、反斜杠: {}",
+                    k, s
+                ));
+            }
+        }
+        if !errors.is_empty() {
+            bail!("主题选项无效: {}", errors.join("；"));
+        }
+        Ok(merged)
+    }
+
+    /// 按继承链叠加复制 `assets/`（先父后子）；返回是否复制了任何资源
+    pub(crate) fn copy_assets(&self, dest: &Path) -> Result<bool> {
+        let mut copied = false;
+        for layer in self.layers.iter().rev() {
+            copied |= layer.copy_assets(dest)?;
+        }
+        Ok(copied)
+    }
+}
+
+/// CSS 颜色：`#rgb`/`#rrggbb`（含透明度）、颜色名，或 `rgb(...)`、`hsl(...)` 等函数
+fn is_css_color(s: &str) -> bool {
+    if let Some(hex) = s.strip_prefix('#') {
+        return matches!(hex.len(), 3 | 4 | 6 | 8) && hex.chars().all(|c| c.is_ascii_hexdigit());
+    }
+    let (name, args) = match s.split_once('(') {
+        Some((name, rest)) => match rest.strip_suffix(')') {
+            Some(args) => (name, Some(args)),
+            None => return false,
+        },
+        None => (s, None),
+    };
+    !name.is_empty()
+        && name.chars().all(|c| c.is_ascii_alphabetic() || c == '-')
+        && args.is_none_or(|a| {
+            a.chars()
+                .all(|c| c.is_ascii_alphanumeric() || " .,%/+-".contains(c))
+        })
+}
+
+/// 可放入 `url('...')` 的图片地址：http(s) 或相对路径，不含引号、括号、反斜杠与控制字符
+fn is_css_url(s: &str) -> bool {
+    let lower = s.to_ascii_lowercase();
+    let allowed_scheme = lower.starts_with("http://") || lower.starts_with("https://");
+    let relative = !s.starts_with("//") && !s.contains(':');
+    (allowed_scheme || relative)
+        && !s
+            .chars()
+            .any(|c| c.is_control() || matches!(c, '\'' | '"' | '(' | ')' | '\\'))
+}
+
+/// CSS 长度：1～4 个以空格分隔的数值，单位可选（如 `12px`、`0.5rem`、`8px 4px`）
+fn is_css_length(s: &str) -> bool {
+    let parts: Vec<&str> = s.split_whitespace().collect();
+    (1..=4).contains(&parts.len())
+        && parts.iter().all(|p| {
+            let unit_start = p
+                .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+                .unwrap_or(p.len());
+            let (num, unit) = p.split_at(unit_start);
+            num.parse::<f64>().is_ok()
+                && (unit.is_empty() || unit == "%" || unit.chars().all(|c| c.is_ascii_alphabetic()))
+        })
+}
+
+/// 选项值类型（用于校验提示）
+fn value_kind(v: &Value) -> &'static str {
+    match v {
+        Value::Null => "空",
+        Value::Bool(_) => "布尔",
+        Value::Number(_) => "数字",
+        Value::String(_) => "字符串",
+        Value::Sequence(_) => "列表",
+        Value::Mapping(_) => "映射",
+        Value::Tagged(_) => "其他",
+    }
+}
+
+/// 解析父主题：纯名称时为同级目录（`themes/<name>`），否则为相对子主题目录的路径；
+/// `default` 在磁盘上不存在时使用内置默认主题
+fn parent_theme(child: &Path, extends: &str) -> Result<ThemeSource> {
+    let is_name = !extends.contains(['/', '\\']) && !extends.starts_with('.');
+    let candidate = if is_name {
+        match child.parent() {
+            Some(p) => p.join(extends),
+            None => PathBuf::from(extends),
+        }
+    } else {
+        child.join(extends)
+    };
+    if candidate.is_dir() {
+        return Ok(ThemeSource::Dir(candidate));
+    }
+    if is_name && extends == EMBEDDED_THEME {
+        return Ok(ThemeSource::Embedded);
+    }
+    bail!(
+        "父主题不存在: {}（由 {} 的 extends 指定）",
+        candidate.display(),
+        child.display()
+    )
+}
+
+/// 列出模板目录下的全部文件：(路径, 以 `/` 分隔的相对名)
+fn template_files(templates: &Path) -> Result<Vec<(PathBuf, String)>> {
+    let mut out = Vec::new();
+    if !templates.is_dir() {
+        return Ok(out);
+    }
+    let pattern = templates.join("**").join("*");
+    let pattern_str = pattern.to_string_lossy().to_string();
+    let paths =
+        glob::glob(&pattern_str).with_context(|| format!("匹配模板失败: {}", pattern_str))?;
+    for path in paths.flatten() {
+        if !path.is_file() {
+            continue;
+        }
+        let Ok(rel) = path.strip_prefix(templates) else {
+            continue;
+        };
+        out.push((path.clone(), slash_path(rel)));
+    }
+    out.sort();
+    Ok(out)
+}
+
+/// 相对路径转为以 `/` 分隔的名称
+fn slash_path(rel: &Path) -> String {
+    let parts: Vec<String> = rel
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().to_string())
+        .collect();
+    parts.join("/")
+}
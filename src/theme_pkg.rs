@@ -0,0 +1,658 @@
+//! 主题包管理模块（`dove theme`）：
+//! - `install`：从本地目录、压缩包（.zip/.tar.gz/.tgz/.tar，可为 URL）或 git 仓库安装主题到 `themes/<name>/`
+//! - `list`：列出已安装的主题（版本、内容哈希、来源），标出未登记与有本地改动的主题
+//! - `update`：按锁文件记录的来源重新获取并安装
+//! - 锁文件 `themes/themes.lock` 记录来源、版本与内容哈希；安装前校验模板齐全（含继承自父主题的模板）
+
+use anyhow::{bail, Context, Result};
+use flate2::read::GzDecoder;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{self, Read},
+    path::{Component, Path, PathBuf},
+    process::Command,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    cli::ThemeAction,
+    init::copy_dir_all,
+    staging::StagingDir,
+    theme::{ThemeChain, ThemeManifest},
+};
+
+/// 锁文件名（位于主题目录下）
+const LOCK_FILE: &str = "themes.lock";
+
+/// 解压上限：条目数与解压后的总字节数（防止压缩炸弹）
+const MAX_ARCHIVE_ENTRIES: usize = 10_000;
+const MAX_ARCHIVE_BYTES: u64 = 256 * 1024 * 1024;
+
+/// 主题必须提供的模板（可由父主题提供）
+const REQUIRED_TEMPLATES: [&str; 2] = ["index.html.tera", "detail.html.tera"];
+
+/// 锁文件
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ThemeLock {
+    #[serde(default)]
+    themes: BTreeMap<String, LockedTheme>,
+}
+
+/// 锁文件中的一条主题记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct LockedTheme {
+    /// 安装来源（路径、压缩包路径/URL 或 git 地址，可带 `#<分支或标签>`）
+    source: String,
+    kind: SourceKind,
+    /// 主题清单中的版本
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    version: Option<String>,
+    /// git 来源的提交哈希
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    rev: Option<String>,
+    /// 安装内容的哈希（`sha256:<hex>`）
+    hash: String,
+    /// 安装时间（RFC 3339）
+    installed_at: String,
+}
+
+/// 主题来源类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum SourceKind {
+    Dir,
+    Archive,
+    Git,
+}
+
+impl ThemeLock {
+    fn load(themes_dir: &Path) -> Result<Self> {
+        let path = themes_dir.join(LOCK_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let text = fs::read_to_string(&path)
+            .with_context(|| format!("读取主题锁文件失败: {}", path.display()))?;
+        serde_yaml::from_str(&text)
+            .with_context(|| format!("解析主题锁文件失败: {}", path.display()))
+    }
+
+    fn save(&self, themes_dir: &Path) -> Result<()> {
+        let path = themes_dir.join(LOCK_FILE);
+        let text = serde_yaml::to_string(self).context("序列化主题锁文件失败")?;
+        let text = format!("# 由 dove theme 生成，请勿手动修改\n{}", text);
+        fs::write(&path, text).with_context(|| format!("写入主题锁文件失败: {}", path.display()))
+    }
+}
+
+/// 执行 `dove theme` 子命令
+pub(crate) fn run_theme(themes_dir: &Path, action: ThemeAction) -> Result<()> {
+    match action {
+        ThemeAction::Install {
+            source,
+            name,
+            force,
+        } => install(themes_dir, &source, name, force),
+        ThemeAction::List => list(themes_dir),
+        ThemeAction::Update { name, force } => update(themes_dir, name, force),
+    }
+}
+
+fn install(themes_dir: &Path, source: &str, name: Option<String>, force: bool) -> Result<()> {
+    let kind = classify(source);
+    let tmp = TempDir::create()?;
+    let fetched = fetch(source, kind, tmp.path())?;
+    let manifest = ThemeManifest::load(&fetched.root)?;
+    let name = match name.or_else(|| manifest.name.clone()) {
+        Some(n) => n,
+        None => default_name(source, kind),
+    };
+    check_name(&name)?;
+    let target = themes_dir.join(&name);
+    if target.exists() && !force {
+        bail!(
+            "主题已存在: {}。使用 --force 覆盖，或运行 dove theme update {}",
+            target.display(),
+            name
+        );
+    }
+    let mut lock = ThemeLock::load(themes_dir)?;
+    let entry = stage(&fetched, &target, source, kind, manifest.version)?;
+    println!(
+        "✅ 已安装主题 {}（{}）-> {}",
+        name,
+        describe_version(&entry),
+        target.display()
+    );
+    lock.themes.insert(name.clone(), entry);
+    lock.save(themes_dir)?;
+    println!(
+        "提示: 在 dove.yaml 中设置 site.theme_dir: {}",
+        target.display()
+    );
+    Ok(())
+}
+
+fn list(themes_dir: &Path) -> Result<()> {
+    let lock = ThemeLock::load(themes_dir)?;
+    let mut dirs: Vec<PathBuf> = Vec::new();
+    if themes_dir.is_dir() {
+        for entry in fs::read_dir(themes_dir)
+            .with_context(|| format!("读取主题目录失败: {}", themes_dir.display()))?
+        {
+            let path = entry?.path();
+            let hidden = path
+                .file_name()
+                .is_some_and(|n| n.to_string_lossy().starts_with('.'));
+            if path.is_dir() && !hidden {
+                dirs.push(path);
+            }
+        }
+    }
+    dirs.sort();
+    for dir in &dirs {
+        let name = dir
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let manifest = ThemeManifest::load(dir).unwrap_or_default();
+        let hash = dir_hash(dir)?;
+        let (source, status) = match lock.themes.get(&name) {
+            Some(e) if e.hash != hash => (e.source.as_str(), "（有本地改动）"),
+            Some(e) => (e.source.as_str(), ""),
+            None => ("本地", "（未登记）"),
+        };
+        let extends = manifest
+            .extends
+            .map(|p| format!(" extends {}", p))
+            .unwrap_or_default();
+        println!(
+            "🎨 {}  {}  {}  {}{}{}",
+            name,
+            manifest.version.as_deref().unwrap_or("-"),
+            short_hash(&hash),
+            source,
+            extends,
+            status
+        );
+    }
+    for name in lock.themes.keys() {
+        if !themes_dir.join(name).is_dir() {
+            eprintln!(
+                "警告: 主题 {} 已登记但目录不存在，可运行 dove theme update {}",
+                name, name
+            );
+        }
+    }
+    if !themes_dir.join("default").is_dir() {
+        println!("🎨 default（内置）");
+    }
+    Ok(())
+}
+
+fn update(themes_dir: &Path, name: Option<String>, force: bool) -> Result<()> {
+    let mut lock = ThemeLock::load(themes_dir)?;
+    let names: Vec<String> = match name {
+        Some(n) if lock.themes.contains_key(&n) => vec![n],
+        Some(n) => bail!(
+            "主题 {} 未在锁文件中登记，请先用 dove theme install 安装",
+            n
+        ),
+        None => lock.themes.keys().cloned().collect(),
+    };
+    if names.is_empty() {
+        println!(
+            "锁文件中没有可更新的主题: {}",
+            themes_dir.join(LOCK_FILE).display()
+        );
+        return Ok(());
+    }
+    let mut failed = 0;
+    for name in names {
+        let old = lock.themes[&name].clone();
+        let target = themes_dir.join(&name);
+        if target.is_dir() && !force && dir_hash(&target)? != old.hash {
+            eprintln!("警告: 跳过 {}：有本地改动（使用 --force 覆盖）", name);
+            continue;
+        }
+        let result = (|| -> Result<Option<LockedTheme>> {
+            let tmp = TempDir::create()?;
+            let fetched = fetch(&old.source, old.kind, tmp.path())?;
+            // 来源未变且已安装内容与之一致（--force 时可能有本地改动）才视为最新
+            let fetched_hash = dir_hash(&fetched.root)?;
+            if fetched_hash == old.hash && target.is_dir() && dir_hash(&target)? == old.hash {
+                return Ok(None);
+            }
+            let manifest = ThemeManifest::load(&fetched.root)?;
+            stage(&fetched, &target, &old.source, old.kind, manifest.version).map(Some)
+        })();
+        match result {
+            Ok(None) => println!("✔ {} 已是最新（{}）", name, describe_version(&old)),
+            Ok(Some(new)) => {
+                let (from, to) = (describe_version(&old), describe_version(&new));
+                if from == to {
+                    println!("♻️ 已重新安装 {}（{}）", name, to);
+                } else {
+                    println!("⬆️ 已更新 {}: {} -> {}", name, from, to);
+                }
+                lock.themes.insert(name, new);
+            }
+            Err(e) => {
+                eprintln!("❌ 更新 {} 失败: {:#}", name, e);
+                failed += 1;
+            }
+        }
+    }
+    lock.save(themes_dir)?;
+    if failed > 0 {
+        bail!("{} 个主题更新失败", failed);
+    }
+    Ok(())
+}
+
+/// 获取到的主题内容
+struct Fetched {
+    /// 主题根目录（含 `templates/`）
+    root: PathBuf,
+    /// git 提交哈希
+    rev: Option<String>,
+}
+
+/// 判断来源类型：压缩包按扩展名识别；git 地址按协议或 `.git` 后缀识别；其余视为本地目录
+fn classify(source: &str) -> SourceKind {
+    let lower = source.to_ascii_lowercase();
+    let path = lower.split(['?', '#']).next().unwrap_or("");
+    if [".zip", ".tar.gz", ".tgz", ".tar"]
+        .iter()
+        .any(|ext| path.ends_with(ext))
+    {
+        return SourceKind::Archive;
+    }
+    let is_url = ["http://", "https://", "git://", "ssh://", "git@", "git+"]
+        .iter()
+        .any(|p| lower.starts_with(p));
+    if is_url || path.ends_with(".git") {
+        SourceKind::Git
+    } else {
+        SourceKind::Dir
+    }
+}
+
+fn fetch(source: &str, kind: SourceKind, tmp: &Path) -> Result<Fetched> {
+    match kind {
+        SourceKind::Dir => {
+            let root = PathBuf::from(source);
+            if !root.is_dir() {
+                bail!("主题目录不存在: {}", source);
+            }
+            Ok(Fetched { root, rev: None })
+        }
+        SourceKind::Archive => {
+            let archive = if source.starts_with("http://") || source.starts_with("https://") {
+                download(source, tmp)?
+            } else {
+                PathBuf::from(source)
+            };
+            if !archive.is_file() {
+                bail!("主题压缩包不存在: {}", archive.display());
+            }
+            let out = tmp.join("extract");
+            fs::create_dir_all(&out)?;
+            extract(&archive, &out)
+                .with_context(|| format!("解压主题失败: {}", archive.display()))?;
+            Ok(Fetched {
+                root: find_theme_root(&out)?,
+                rev: None,
+            })
+        }
+        SourceKind::Git => {
+            let (url, reference) = match source.rsplit_once('#') {
+                Some((u, r)) if !r.is_empty() => (u, Some(r)),
+                _ => (source, None),
+            };
+            let url = url.strip_prefix("git+").unwrap_or(url);
+            let repo = tmp.join("repo");
+            let mut clone = Command::new("git");
+            clone.args(["clone", "--quiet", "--depth", "1"]);
+            if let Some(r) = reference {
+                clone.args(["--branch", r]);
+            }
+            clone.arg("--").arg(url).arg(&repo);
+            run(&mut clone, &format!("克隆主题仓库失败: {}", source))?;
+            let rev = Command::new("git")
+                .arg("-C")
+                .arg(&repo)
+                .args(["rev-parse", "HEAD"])
+                .output()
+                .ok()
+                .filter(|o| o.status.success())
+                .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string());
+            // 仓库元数据不属于主题内容
+            let _ = fs::remove_dir_all(repo.join(".git"));
+            Ok(Fetched {
+                root: find_theme_root(&repo)?,
+                rev,
+            })
+        }
+    }
+}
+
+#[cfg(feature = "remote")]
+fn download(url: &str, tmp: &Path) -> Result<PathBuf> {
+    let file_name = url
+        .split(['?', '#'])
+        .next()
+        .and_then(|u| u.rsplit('/').next())
+        .filter(|n| !n.is_empty())
+        .unwrap_or("theme.zip");
+    let path = tmp.join(file_name);
+    let resp = ureq::get(url)
+        .set("User-Agent", "dove/0.1")
+        .call()
+        .with_context(|| format!("下载主题失败: {}", url))?;
+    let mut file =
+        fs::File::create(&path).with_context(|| format!("创建文件失败: {}", path.display()))?;
+    std::io::copy(&mut resp.into_reader(), &mut file)
+        .with_context(|| format!("下载主题失败: {}", url))?;
+    Ok(path)
+}
+
+#[cfg(not(feature = "remote"))]
+fn download(url: &str, _tmp: &Path) -> Result<PathBuf> {
+    bail!(
+        "下载主题压缩包需要启用 remote 特性（--features remote）；也可先下载到本地再安装: {}",
+        url
+    )
+}
+
+/// 解压 .zip/.tar.gz/.tgz/.tar 到 `out`；条目路径越出目标目录或为链接、
+/// 条目数或解压总大小超出上限时报错
+fn extract(archive: &Path, out: &Path) -> Result<()> {
+    let file =
+        fs::File::open(archive).with_context(|| format!("打开文件失败: {}", archive.display()))?;
+    let lower = archive.to_string_lossy().to_ascii_lowercase();
+    if lower.ends_with(".zip") {
+        let mut zip = zip::ZipArchive::new(file).context("读取 zip 失败")?;
+        if zip.len() > MAX_ARCHIVE_ENTRIES {
+            bail!("压缩包条目超过 {} 个上限", MAX_ARCHIVE_ENTRIES);
+        }
+        let mut budget = MAX_ARCHIVE_BYTES;
+        for i in 0..zip.len() {
+            let mut entry = zip.by_index(i).context("读取 zip 条目失败")?;
+            let Some(rel) = entry_path(Path::new(entry.name())) else {
+                bail!("压缩包中的路径越出目标目录: {}", entry.name());
+            };
+            if entry.is_symlink() {
+                bail!("压缩包中包含不支持的链接: {}", entry.name());
+            }
+            let dest = out.join(rel);
+            if entry.is_dir() {
+                fs::create_dir_all(&dest)?;
+                continue;
+            }
+            let name = entry.name().to_string();
+            write_limited(&mut entry, &dest, &mut budget)
+                .with_context(|| format!("解压条目失败: {}", name))?;
+        }
+        return Ok(());
+    }
+    let reader: Box<dyn Read> = if lower.ends_with(".tar.gz") || lower.ends_with(".tgz") {
+        Box::new(GzDecoder::new(file))
+    } else {
+        Box::new(file)
+    };
+    let mut tar = tar::Archive::new(reader);
+    let mut budget = MAX_ARCHIVE_BYTES;
+    for (i, entry) in tar.entries().context("读取 tar 失败")?.enumerate() {
+        if i >= MAX_ARCHIVE_ENTRIES {
+            bail!("压缩包条目超过 {} 个上限", MAX_ARCHIVE_ENTRIES);
+        }
+        let mut entry = entry.context("读取 tar 条目失败")?;
+        let name = entry.path()?.to_path_buf();
+        let Some(rel) = entry_path(&name) else {
+            bail!("压缩包中的路径越出目标目录: {}", name.display());
+        };
+        let kind = entry.header().entry_type();
+        if kind.is_symlink() || kind.is_hard_link() {
+            bail!("压缩包中包含不支持的链接: {}", name.display());
+        }
+        // 仅解出目录与普通文件（pax 扩展头等由 tar 自行处理）
+        if !(kind.is_dir() || kind.is_file()) {
+            continue;
+        }
+        let dest = out.join(rel);
+        if kind.is_dir() {
+            fs::create_dir_all(&dest)?;
+            continue;
+        }
+        write_limited(&mut entry, &dest, &mut budget)
+            .with_context(|| format!("解压条目失败: {}", name.display()))?;
+    }
+    Ok(())
+}
+
+/// 写出一个解压条目，按实际写入的字节数扣减剩余额度（不信任压缩包中声明的大小）
+fn write_limited(entry: &mut impl Read, dest: &Path, budget: &mut u64) -> Result<()> {
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut f =
+        fs::File::create(dest).with_context(|| format!("创建文件失败: {}", dest.display()))?;
+    let written = io::copy(&mut entry.take(*budget + 1), &mut f)?;
+    if written > *budget {
+        bail!(
+            "压缩包解压后超过 {} MB 上限",
+            MAX_ARCHIVE_BYTES / 1024 / 1024
+        );
+    }
+    *budget -= written;
+    Ok(())
+}
+
+/// 压缩包条目的相对路径：只允许普通路径段（忽略 `.`），绝对路径与 `..` 返回 None
+fn entry_path(name: &Path) -> Option<PathBuf> {
+    let mut rel = PathBuf::new();
+    for c in name.components() {
+        match c {
+            Component::Normal(seg) => rel.push(seg),
+            Component::CurDir => {}
+            _ => return None,
+        }
+    }
+    Some(rel)
+}
+
+fn run(cmd: &mut Command, what: &str) -> Result<()> {
+    let output = cmd
+        .output()
+        .with_context(|| format!("{}（无法执行 {:?}）", what, cmd.get_program()))?;
+    if !output.status.success() {
+        bail!(
+            "{}: {}",
+            what,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
+
+/// 定位主题根目录：压缩包/仓库常把主题放在唯一的顶层子目录中
+fn find_theme_root(dir: &Path) -> Result<PathBuf> {
+    let is_theme = |d: &Path| {
+        d.join("templates").is_dir()
+            || d.join("theme.toml").is_file()
+            || d.join("theme.yaml").is_file()
+    };
+    if is_theme(dir) {
+        return Ok(dir.to_path_buf());
+    }
+    let mut subdirs = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        if path.is_dir() && !name.starts_with('.') && name != "__MACOSX" {
+            subdirs.push(path);
+        }
+    }
+    match subdirs.as_slice() {
+        [only] if is_theme(only) => Ok(only.clone()),
+        _ => Ok(dir.to_path_buf()),
+    }
+}
+
+/// 复制到暂存目录、校验模板并整体替换目标目录；返回锁文件记录
+fn stage(
+    fetched: &Fetched,
+    target: &Path,
+    source: &str,
+    kind: SourceKind,
+    version: Option<String>,
+) -> Result<LockedTheme> {
+    let staging = StagingDir::create(target)?;
+    copy_dir_all(&fetched.root, staging.path())?;
+    // 暂存目录名不是主题名：按安装后的目录名校验（清单未声明 name 时）
+    let name = target.file_name().map(|n| n.to_string_lossy().to_string());
+    validate(staging.path(), name.as_deref())?;
+    let hash = dir_hash(staging.path())?;
+    staging.commit()?;
+    Ok(LockedTheme {
+        source: source.to_string(),
+        kind,
+        version,
+        rev: fetched.rev.clone(),
+        hash,
+        installed_at: chrono::Local::now().to_rfc3339(),
+    })
+}
+
+/// 校验主题（含继承链）提供了必需的模板，且全部模板能被解析
+fn validate(dir: &Path, name: Option<&str>) -> Result<()> {
+    let chain = ThemeChain::resolve_as(dir, name)?;
+    let tera = chain.load_templates()?;
+    let names: Vec<&str> = tera.get_template_names().collect();
+    let missing: Vec<String> = REQUIRED_TEMPLATES
+        .iter()
+        .filter(|t| !names.contains(t))
+        .map(|t| format!("templates/{}", t))
+        .collect();
+    if !missing.is_empty() {
+        bail!("主题缺少必需的模板: {}", missing.join(", "));
+    }
+    Ok(())
+}
+
+/// 目录内容哈希：按相对路径排序，依次计入路径与文件内容
+fn dir_hash(dir: &Path) -> Result<String> {
+    fn walk(dir: &Path, rel: &str, out: &mut Vec<(String, PathBuf)>) -> Result<()> {
+        for entry in
+            fs::read_dir(dir).with_context(|| format!("读取目录失败: {}", dir.display()))?
+        {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().to_string();
+            let rel = if rel.is_empty() {
+                name
+            } else {
+                format!("{}/{}", rel, name)
+            };
+            if entry.file_type()?.is_dir() {
+                walk(&entry.path(), &rel, out)?;
+            } else {
+                out.push((rel, entry.path()));
+            }
+        }
+        Ok(())
+    }
+    let mut files = Vec::new();
+    walk(dir, "", &mut files)?;
+    files.sort();
+    let mut hasher = Sha256::new();
+    for (rel, path) in files {
+        let data = fs::read(&path).with_context(|| format!("读取文件失败: {}", path.display()))?;
+        hasher.update(rel.as_bytes());
+        hasher.update([0]);
+        hasher.update((data.len() as u64).to_le_bytes());
+        hasher.update(&data);
+    }
+    let hex: String = hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    Ok(format!("sha256:{}", hex))
+}
+
+fn short_hash(hash: &str) -> &str {
+    let hex = hash.strip_prefix("sha256:").unwrap_or(hash);
+    &hex[..hex.len().min(12)]
+}
+
+/// 版本描述：清单版本 > git 提交 > 内容哈希
+fn describe_version(entry: &LockedTheme) -> String {
+    match (&entry.version, &entry.rev) {
+        (Some(v), _) => v.clone(),
+        (None, Some(rev)) => rev[..rev.len().min(12)].to_string(),
+        (None, None) => short_hash(&entry.hash).to_string(),
+    }
+}
+
+/// 由来源推导主题名：目录名、压缩包文件名（去扩展名）或仓库名
+fn default_name(source: &str, kind: SourceKind) -> String {
+    let trimmed = source
+        .split(['?', '#'])
+        .next()
+        .unwrap_or(source)
+        .trim_end_matches(['/', '\\']);
+    let last = trimmed.rsplit(['/', '\\', ':']).next().unwrap_or(trimmed);
+    let lower = last.to_ascii_lowercase();
+    let strip = match kind {
+        SourceKind::Archive => [".tar.gz", ".tgz", ".zip", ".tar"]
+            .iter()
+            .find(|ext| lower.ends_with(*ext))
+            .map(|ext| ext.len())
+            .unwrap_or(0),
+        SourceKind::Git if lower.ends_with(".git") => 4,
+        _ => 0,
+    };
+    last[..last.len() - strip].to_string()
+}
+
+/// 主题名必须是单级目录名
+fn check_name(name: &str) -> Result<()> {
+    if name.is_empty() || name.starts_with('.') || name.contains(['/', '\\']) || name == LOCK_FILE {
+        bail!("主题名无效: {:?}（可用 --name 指定）", name);
+    }
+    Ok(())
+}
+
+/// 临时目录：离开作用域时删除
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn create() -> Result<Self> {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or(0);
+        let path =
+            std::env::temp_dir().join(format!("dove-theme-{}-{}", std::process::id(), nanos));
+        fs::create_dir_all(&path)
+            .with_context(|| format!("创建临时目录失败: {}", path.display()))?;
+        Ok(Self(path))
+    }
+
+    fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
@@ -2,6 +2,7 @@
 //! - 环境变量读取与解析
 //! - 安全的子路径处理、URL 主机名提取
 //! - 文本到枚举的解析工具
+//! - FNV-1a 哈希（图标文件名、资源指纹）
 
 use crate::config::ColorScheme;
 use std::{env, path::PathBuf};
@@ -73,3 +74,15 @@ pub(crate) fn hostname_from_url(u: &str) -> Option<String> {
         Err(_) => None,
     }
 }
+
+/// FNV-1a 64 位哈希（非加密用途，仅用于生成稳定的文件名）。
+pub(crate) fn fnv1a64(data: &[u8]) -> u64 {
+    const FNV_OFFSET: u64 = 0xcbf29ce484222325;
+    const FNV_PRIME: u64 = 0x00000100000001b3;
+    let mut hash = FNV_OFFSET;
+    for b in data {
+        hash ^= *b as u64;
+        hash = hash.wrapping_mul(FNV_PRIME);
+    }
+    hash
+}
@@ -373,6 +373,7 @@
     return `${y}年${m}月${day}日`;
   }
   function lunarString(d) {
+    if (document.body.dataset.lunar === 'off') return '';
     try {
       const fmt = new Intl.DateTimeFormat('zh-CN-u-ca-chinese', { month: 'long', day: 'numeric' });
       const s = fmt.format(d);
@@ -707,6 +708,8 @@
     // setup double buffer and default tone
     // ensureBgBuffers(); // 注释掉未定义的函数调用
     applyTone('dark');
+    // 主题选项 background_image：使用固定背景，不再轮换
+    if (document.body.dataset.bgImage) return;
     // initial load
     updateBg();
     if (bgNextBtn) { bgNextBtn.addEventListener('click', () => updateBg()); }
@@ -1,20 +1,27 @@
 // Service Worker for offline caching
-const CACHE_NAME = 'dove-site-v2';
+// dove build injects `self.DOVE_BUILD = { hash, assets, precache }` at the top of this file:
+// `hash` changes whenever any emitted page or asset changes, `assets` maps original paths to
+// fingerprinted ones, and `precache` lists every emitted file as `{ url, revision }`.
+const BUILD = self.DOVE_BUILD || { hash: 'dev', assets: {}, precache: null };
+const CACHE_NAME = 'dove-site-' + BUILD.hash;
 // Derive site root from SW location to support base_path
-const ROOT = new URL('../', self.location).pathname; // e.g. '/base_path/'
+const ROOT = new URL('./', self.location).pathname; // e.g. '/base_path/'
 const p = (rel) => ROOT + String(rel).replace(/^\/+/,'');
+const a = (rel) => p(BUILD.assets[rel] || rel);
 const OFFLINE_URL = p('assets/offline.html');
-const urlsToCache = [
-  p(''),
-  p('intranet/'),
-  p('assets/styles.css'),
-  p('assets/app.js'),
-  p('assets/qrcode.min.js'),
-  p('assets/offline.html'),
-  p('assets/favicon.svg'),
-  p('assets/favicon.png'),
-  p('assets/favicon-f.svg')
-];
+const urlsToCache = BUILD.precache
+  ? BUILD.precache.map(entry => p(entry.url))
+  : [
+      p(''),
+      p('intranet/'),
+      a('assets/styles.css'),
+      a('assets/app.js'),
+      a('assets/qrcode.min.js'),
+      p('assets/offline.html'),
+      a('assets/favicon.svg'),
+      a('assets/favicon.png'),
+      a('assets/favicon-f.svg')
+    ];
 
 // Install event - cache static assets
 self.addEventListener('install', event => {
@@ -22,7 +29,7 @@ self.addEventListener('install', event => {
     caches.open(CACHE_NAME)
       .then(cache => {
         console.log('Opened cache');
-        return cache.addAll(urlsToCache);
+        return cache.addAll(urlsToCache.map(url => new Request(url, { cache: 'reload' })));
       })
       .then(() => self.skipWaiting())
   );